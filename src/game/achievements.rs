@@ -0,0 +1,159 @@
+//! Module with Achievement enum and AchievementTracker struct, unlocking them from game events
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use crate::game::{
+    consts, death::DeathCause, event::GameEvent, food::FoodKind, stats::SessionStats,
+};
+
+/// Enumeration of all goals the player can reach.
+/// Once unlocked, an achievement stays unlocked in every later session.
+///
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Achievement {
+    FirstFood,
+    Score100,
+    Score500,
+    Score1000,
+    Length25,
+    Length50,
+    Length100,
+    SurviveMinute,
+    SurviveFiveMinutes,
+    EatWhileGhost,
+    EatSpeedBoost,
+    EatShrink,
+    EatScoreDouble,
+    BigBite,
+    OutlastPowerUp,
+    ClearLevel,
+    DieByHunger,
+    DieByWall,
+    BiteYourself,
+    FallIntoVoid,
+    Play10Games,
+    Play100Games,
+    TotalScore10000,
+}
+
+/// Structure that remembers the unlocked achievements
+/// and checks every game event for the new ones.
+///
+/// Unlocked achievements are kept as a JSON file next to the high score file.
+/// Whether the ghost power-up is active is only tracked during the run.
+///
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct AchievementTracker {
+    pub unlocked: HashSet<Achievement>,
+    #[serde(skip)]
+    is_ghost: bool,
+}
+
+impl AchievementTracker {
+    /// Read unlocked achievements from the file. Missing or malformed file counts as none.
+    ///
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write unlocked achievements into the file, creating missing directories
+    ///
+    pub fn save(&self) {
+        if let Some(path) = Self::path() {
+            let content =
+                serde_json::to_string(self).expect("Error while serializing achievements");
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, content));
+            if let Err(err) = written {
+                eprintln!("Could not save achievements to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Return achievements that were unlocked by the event, skipping the ones unlocked before.
+    /// `stats` should already include the run that has just ended.
+    ///
+    pub fn check(&mut self, event: &GameEvent, stats: &SessionStats) -> Vec<Achievement> {
+        let reached = self.reached(event, stats);
+        reached
+            .into_iter()
+            .filter(|&achievement| self.unlocked.insert(achievement))
+            .collect()
+    }
+
+    fn reached(&mut self, event: &GameEvent, stats: &SessionStats) -> Vec<Achievement> {
+        let mut reached = Vec::new();
+        match event {
+            GameEvent::FoodEaten { kind, score, .. } => {
+                reached.push(Achievement::FirstFood);
+                if self.is_ghost {
+                    reached.push(Achievement::EatWhileGhost);
+                }
+                match kind {
+                    FoodKind::SpeedBoost => reached.push(Achievement::EatSpeedBoost),
+                    FoodKind::Shrink => reached.push(Achievement::EatShrink),
+                    FoodKind::ScoreDouble => reached.push(Achievement::EatScoreDouble),
+                    FoodKind::Normal
+                    | FoodKind::Ghost
+                    | FoodKind::Shield
+                    | FoodKind::Confusion
+                    | FoodKind::Freeze
+                    | FoodKind::Magnet => {}
+                }
+                if *score >= consts::BIG_BITE_SCORE {
+                    reached.push(Achievement::BigBite);
+                }
+            }
+            GameEvent::SnakeDied { cause, .. } => {
+                self.is_ghost = false;
+                match cause {
+                    DeathCause::Hunger => reached.push(Achievement::DieByHunger),
+                    DeathCause::Wall => reached.push(Achievement::DieByWall),
+                    DeathCause::SelfCollision => reached.push(Achievement::BiteYourself),
+                    DeathCause::Void => reached.push(Achievement::FallIntoVoid),
+                    DeathCause::Obstacle(_) | DeathCause::OtherSnake | DeathCause::TimeUp => {}
+                }
+                let games = stats.games_played;
+                let goals = [
+                    (games >= 10, Achievement::Play10Games),
+                    (games >= 100, Achievement::Play100Games),
+                    (stats.total_score >= 10_000, Achievement::TotalScore10000),
+                ];
+                reached.extend(goals.iter().filter(|(done, _)| *done).map(|&(_, a)| a));
+            }
+            GameEvent::LevelComplete => reached.push(Achievement::ClearLevel),
+            GameEvent::PowerUpExpired { .. } => reached.push(Achievement::OutlastPowerUp),
+            GameEvent::StepFinished {
+                score,
+                length,
+                run_time,
+                is_ghost,
+            } => {
+                self.is_ghost = *is_ghost;
+                let widths = length / consts::SNAKE_WIDTH;
+                let goals = [
+                    (*score >= 100, Achievement::Score100),
+                    (*score >= 500, Achievement::Score500),
+                    (*score >= 1000, Achievement::Score1000),
+                    (widths >= 25., Achievement::Length25),
+                    (widths >= 50., Achievement::Length50),
+                    (widths >= 100., Achievement::Length100),
+                    (*run_time >= 60., Achievement::SurviveMinute),
+                    (*run_time >= 300., Achievement::SurviveFiveMinutes),
+                ];
+                reached.extend(goals.iter().filter(|(done, _)| *done).map(|&(_, a)| a));
+            }
+        }
+        reached
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::ACHIEVEMENTS_FILE))
+    }
+}
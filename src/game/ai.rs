@@ -0,0 +1,363 @@
+//! Module with the AI that plays the game on its own
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use crate::game::{consts, coords::Coords, direction::Direction, food::Food, snake::Snake};
+use ggez::graphics::Rect;
+
+/// Position of a single square cell of the board, each one `consts::SNAKE_WIDTH` wide
+///
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct GridCell {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl GridCell {
+    /// Create `GridCell` that contains the given point
+    ///
+    pub fn from_coords(pos: Coords) -> Self {
+        let (x, y) = pos.grid_cell(consts::SNAKE_WIDTH);
+        Self { x, y }
+    }
+
+    /// Return the neighbouring cell in the given direction
+    ///
+    pub fn step(self, dir: Direction) -> Self {
+        let offset = dir.as_coords();
+        Self {
+            x: self.x + offset.x as i32,
+            y: self.y + offset.y as i32,
+        }
+    }
+
+    /// Return the direction to a neighbouring cell
+    ///
+    pub fn direction_to(self, other: Self) -> Option<Direction> {
+        Direction::all()
+            .iter()
+            .copied()
+            .find(|&dir| self.step(dir) == other)
+    }
+
+    /// Return the number of steps between cells, when nothing is in the way
+    ///
+    pub fn manhattan(self, other: Self) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+/// Board split into cells, with the ones that cannot be entered marked
+///
+#[derive(Default, Debug)]
+pub struct GridMap {
+    pub width: i32,
+    pub height: i32,
+    pub blocked: Vec<bool>,
+}
+
+impl GridMap {
+    /// Create `GridMap` of a given size, with all the cells free
+    ///
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            blocked: vec![false; (width * height).max(0) as usize],
+        }
+    }
+
+    /// Create `GridMap` covering the screen, with cells taken by snake segments
+    /// and `blocked` areas, like obstacles, made impossible to enter.
+    /// Cell right behind the head is blocked as well, as the snake cannot turn back.
+    ///
+    pub fn from_snake(snake: &Snake, blocked: &[Rect], screen: Coords) -> Self {
+        let mut grid = Self::new(
+            (screen.x / consts::SNAKE_WIDTH) as i32,
+            (screen.y / consts::SNAKE_WIDTH) as i32,
+        );
+        for area in snake
+            .get_all_bboxes()
+            .into_iter()
+            .chain(blocked.iter().copied())
+        {
+            grid.block_rect(area);
+        }
+
+        let head = GridCell::from_coords(snake.head_position());
+        grid.block(head.step(snake.dir.opposite()));
+        grid
+    }
+
+    /// Check if the cell lies on the board
+    ///
+    pub fn is_inside(&self, cell: GridCell) -> bool {
+        cell.x >= 0 && cell.y >= 0 && cell.x < self.width && cell.y < self.height
+    }
+
+    /// Check if the cell lies on the board and can be entered
+    ///
+    pub fn is_free(&self, cell: GridCell) -> bool {
+        self.is_inside(cell) && !self.blocked[self.index(cell)]
+    }
+
+    /// Mark the cell as impossible to enter, cells outside the board are ignored
+    ///
+    pub fn block(&mut self, cell: GridCell) {
+        if self.is_inside(cell) {
+            let idx = self.index(cell);
+            self.blocked[idx] = true;
+        }
+    }
+
+    /// Mark all cells overlapped by `rect` as impossible to enter
+    ///
+    pub fn block_rect(&mut self, rect: Rect) {
+        // shrunk by a pixel, so a rect lying exactly on a cell edge takes only one cell
+        let from = GridCell::from_coords(Coords::new(rect.left() + 1., rect.top() + 1.));
+        let to = GridCell::from_coords(Coords::new(rect.right() - 1., rect.bottom() - 1.));
+        for y in from.y..=to.y {
+            for x in from.x..=to.x {
+                self.block(GridCell { x, y });
+            }
+        }
+    }
+
+    fn index(&self, cell: GridCell) -> usize {
+        (cell.y * self.width + cell.x) as usize
+    }
+}
+
+/// Find the shortest route from `from` to `to` with A*, going only through free cells.
+///
+/// Returned path does not contain `from`, but ends with `to`.
+/// `from` itself does not have to be free, as it is usually the snake head.
+///
+pub fn find_path(grid: &GridMap, from: GridCell, to: GridCell) -> Option<Vec<GridCell>> {
+    if !grid.is_inside(from) || !grid.is_free(to) {
+        return None;
+    }
+
+    let mut came_from: Vec<Option<GridCell>> = vec![None; grid.blocked.len()];
+    let mut cost = vec![i32::MAX; grid.blocked.len()];
+    let mut open = BinaryHeap::new();
+    cost[grid.index(from)] = 0;
+    open.push(Reverse((from.manhattan(to), 0, from)));
+
+    while let Some(Reverse((_, from_cost, cell))) = open.pop() {
+        if cell == to {
+            let mut path = vec![cell];
+            let mut current = cell;
+            while let Some(prev) = came_from[grid.index(current)] {
+                if prev == from {
+                    break;
+                }
+                path.push(prev);
+                current = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // already reached in fewer steps
+        if from_cost > cost[grid.index(cell)] {
+            continue;
+        }
+
+        for &dir in &Direction::all() {
+            let next = cell.step(dir);
+            let next_cost = from_cost + 1;
+            if grid.is_free(next) && next_cost < cost[grid.index(next)] {
+                cost[grid.index(next)] = next_cost;
+                came_from[grid.index(next)] = Some(cell);
+                open.push(Reverse((next_cost + next.manhattan(to), next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
+
+/// Structure that steers the snake towards food, in `GameMode::AutoPlay`.
+///
+/// Board is kept as a `GridMap` rebuilt from the snake on every decision.
+/// Moves that would leave the snake less free cells than its own length are avoided,
+/// as they most likely lead into a dead end.
+///
+#[derive(Default, Debug)]
+pub struct AiController {
+    pub grid: GridMap,
+}
+
+impl AiController {
+    /// Return the direction the snake should go next, to reach `food`.
+    ///
+    /// Follows the first step of the A* path from the head cell, when it is safe.
+    /// Otherwise picks a safe move that still reaches the food with the shortest path.
+    /// When there is no such move, goes where the most free cells can be reached.
+    ///
+    pub fn next_direction(
+        &mut self,
+        snake: &Snake,
+        food: &Food,
+        blocked: &[Rect],
+        screen: Coords,
+    ) -> Direction {
+        self.grid = GridMap::from_snake(snake, blocked, screen);
+        let head = GridCell::from_coords(snake.head_position());
+        let goal = GridCell::from_coords(food.center());
+        let snake_len = snake.length();
+        let needed_cells = (snake_len / consts::SNAKE_WIDTH).ceil() as usize;
+
+        let best = find_path(&self.grid, head, goal)
+            .and_then(|path| path.first().and_then(|&next| head.direction_to(next)));
+        if let Some(dir) = best {
+            if Self::safe_move_count(&self.grid, head.step(dir)) >= needed_cells {
+                return dir;
+            }
+        }
+
+        // direction, free cells reachable after the move and path length to the food
+        let candidates: Vec<(Direction, usize, Option<usize>)> =
+            [snake.dir, snake.dir.rotate_cw(), snake.dir.rotate_ccw()]
+                .iter()
+                .copied()
+                .filter(|&dir| Some(dir) != best && self.grid.is_free(head.step(dir)))
+                .map(|dir| {
+                    let next = head.step(dir);
+                    let path_len = find_path(&self.grid, next, goal).map(|path| path.len());
+                    (dir, Self::safe_move_count(&self.grid, next), path_len)
+                })
+                .collect();
+
+        let safe_to_food = candidates
+            .iter()
+            .filter(|&&(_, reachable, path_len)| reachable >= needed_cells && path_len.is_some())
+            .min_by_key(|&&(_, _, path_len)| path_len);
+        let most_space = candidates
+            .iter()
+            .max_by_key(|&&(_, reachable, _)| reachable);
+
+        safe_to_food
+            .or(most_space)
+            .map(|&(dir, _, _)| dir)
+            .or(best)
+            .unwrap_or(snake.dir)
+    }
+
+    /// Count free cells that can be reached from `from` with a flood fill, including `from`.
+    /// Returns 0 when `from` itself is not free.
+    ///
+    pub fn safe_move_count(grid: &GridMap, from: GridCell) -> usize {
+        if !grid.is_free(from) {
+            return 0;
+        }
+
+        let mut visited = vec![false; grid.blocked.len()];
+        let mut queue = VecDeque::new();
+        visited[grid.index(from)] = true;
+        queue.push_back(from);
+        let mut count = 0;
+        while let Some(cell) = queue.pop_front() {
+            count += 1;
+            for &dir in &Direction::all() {
+                let next = cell.step(dir);
+                if grid.is_free(next) && !visited[grid.index(next)] {
+                    visited[grid.index(next)] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn default_board() -> GridMap {
+        GridMap::new(
+            (consts::SCREEN_SIZE.x / consts::SNAKE_WIDTH) as i32,
+            (consts::SCREEN_SIZE.y / consts::SNAKE_WIDTH) as i32,
+        )
+    }
+
+    /// Board with walls on every other column, leaving a gap alternately
+    /// at the bottom and at the top, so the only route snakes across all of it
+    ///
+    fn serpentine_board() -> GridMap {
+        let mut grid = default_board();
+        for x in (1..grid.width - 1).step_by(2) {
+            let gap = if x % 4 == 1 { grid.height - 1 } else { 0 };
+            for y in (0..grid.height).filter(|&y| y != gap) {
+                grid.block(GridCell { x, y });
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn path_on_an_empty_board_is_as_long_as_the_distance() {
+        let grid = default_board();
+        let from = GridCell { x: 3, y: 4 };
+        let to = GridCell { x: 10, y: 1 };
+        let path = find_path(&grid, from, to).unwrap();
+        assert_eq!(path.len() as i32, from.manhattan(to));
+        assert_eq!(path.last(), Some(&to));
+        assert!(!path.contains(&from));
+    }
+
+    #[test]
+    fn path_steps_only_through_free_neighbouring_cells() {
+        let grid = serpentine_board();
+        let from = GridCell { x: 0, y: 0 };
+        let to = GridCell {
+            x: grid.width - 1,
+            y: grid.height - 1,
+        };
+        let path = find_path(&grid, from, to).unwrap();
+        let mut prev = from;
+        for &cell in &path {
+            assert!(grid.is_free(cell));
+            assert!(prev.direction_to(cell).is_some());
+            prev = cell;
+        }
+        assert_eq!(prev, to);
+    }
+
+    #[test]
+    fn no_path_to_a_blocked_or_walled_off_cell() {
+        let mut grid = default_board();
+        let from = GridCell { x: 0, y: 0 };
+        grid.block(GridCell { x: 5, y: 5 });
+        assert_eq!(find_path(&grid, from, GridCell { x: 5, y: 5 }), None);
+
+        for &dir in &Direction::all() {
+            grid.block(GridCell { x: 10, y: 10 }.step(dir));
+        }
+        assert_eq!(find_path(&grid, from, GridCell { x: 10, y: 10 }), None);
+    }
+
+    #[test]
+    #[ignore = "timing, run with `cargo test --release -- --ignored`"]
+    fn path_on_the_default_board_takes_under_a_millisecond() {
+        const RUNS: u32 = 100;
+
+        let grid = serpentine_board();
+        let from = GridCell { x: 0, y: 0 };
+        let to = GridCell {
+            x: grid.width - 1,
+            y: grid.height - 1,
+        };
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            assert!(find_path(&grid, from, to).is_some());
+        }
+        let per_path = start.elapsed() / RUNS;
+        assert!(per_path.as_micros() < 1000, "took {:?}", per_path);
+    }
+}
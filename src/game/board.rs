@@ -0,0 +1,288 @@
+//! Module with Board struct, managing terrain tiles of the play area
+use ggez::{
+    graphics::{self, Color, Rect},
+    Context,
+};
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+use serde::Deserialize;
+use std::collections::VecDeque;
+
+use crate::game::{consts, coords::Coords};
+
+/// Enumeration of terrain kinds, each one affects the snake in its own way
+///
+#[derive(PartialEq, Clone, Copy, Debug, Deserialize)]
+pub enum TileKind {
+    Normal,
+    /// Snake slides faster and turns with a delay
+    Ice,
+    /// Snake crawls with half of the speed
+    Mud,
+    /// Snake falls into the void and dies
+    Void,
+    /// Maze wall, it turns into an `Obstacle`
+    Wall,
+}
+
+impl TileKind {
+    /// Return value by which the snake speed is multiplied on this tile
+    ///
+    pub fn speed_modifier(&self) -> f32 {
+        match self {
+            TileKind::Normal | TileKind::Void | TileKind::Wall => 1.,
+            TileKind::Ice => 1.25,
+            TileKind::Mud => 0.5,
+        }
+    }
+
+    /// Return faint color of the overlay the tile is drawn with, if it is drawn at all
+    ///
+    pub fn overlay_color(&self) -> Option<Color> {
+        match self {
+            // walls are drawn as obstacles
+            TileKind::Normal | TileKind::Wall => None,
+            TileKind::Ice => Some(Color::new(0.7, 0.9, 1., 0.3)),
+            TileKind::Mud => Some(Color::new(0.4, 0.25, 0.1, 0.4)),
+            TileKind::Void => Some(Color::new(0., 0., 0., 0.7)),
+        }
+    }
+}
+
+/// Single square cell of the `Board`
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Tile {
+    pub kind: TileKind,
+}
+
+impl Default for Tile {
+    fn default() -> Self {
+        Self {
+            kind: TileKind::Normal,
+        }
+    }
+}
+
+/// Structure holding terrain of the play area, as rows of square tiles
+/// that are `cell_size` wide.
+///
+#[derive(PartialEq, Clone, Debug)]
+pub struct Board {
+    pub tiles: Vec<Vec<Tile>>,
+    pub cell_size: f32,
+}
+
+impl Board {
+    /// Create `Board` of normal tiles, covering the screen of the given size
+    ///
+    pub fn new(screen: Coords, cell_size: f32) -> Self {
+        let width = (screen.x / cell_size).ceil() as usize;
+        let height = (screen.y / cell_size).ceil() as usize;
+        Self {
+            tiles: vec![vec![Tile::default(); width]; height],
+            cell_size,
+        }
+    }
+
+    /// Change kind of all tiles that overlap `area`
+    ///
+    pub fn fill(&mut self, area: Rect, kind: TileKind) {
+        let (first_col, first_row) = Coords::new(area.left(), area.top()).grid_cell(self.cell_size);
+        let (first_col, first_row) = (first_col.max(0) as usize, first_row.max(0) as usize);
+        let last_col = (area.right() / self.cell_size).ceil().max(0.) as usize;
+        let last_row = (area.bottom() / self.cell_size).ceil().max(0.) as usize;
+        for row in self.tiles.iter_mut().take(last_row).skip(first_row) {
+            for tile in row.iter_mut().take(last_col).skip(first_col) {
+                tile.kind = kind;
+            }
+        }
+    }
+
+    /// Return kind of the tile under `pos`.
+    /// Everything outside of the board counts as normal terrain.
+    ///
+    pub fn tile_at(&self, pos: Coords) -> TileKind {
+        if pos.x < 0. || pos.y < 0. {
+            return TileKind::Normal;
+        }
+
+        let (col, row) = pos.grid_cell(self.cell_size);
+        self.tiles
+            .get(row as usize)
+            .and_then(|tiles| tiles.get(col as usize))
+            .map_or(TileKind::Normal, |tile| tile.kind)
+    }
+
+    /// Return areas of all tiles of the given kind
+    ///
+    pub fn areas_of(&self, kind: TileKind) -> Vec<Rect> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(row, tiles)| {
+                tiles
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, tile)| tile.kind == kind)
+                    .map(move |(col, _)| self.cell_rect(row, col))
+            })
+            .collect()
+    }
+
+    /// Draws all tiles other than normal ones as faint overlaid rectangles
+    ///
+    pub fn draw(&self, ctx: &mut Context) {
+        let mut builder = graphics::MeshBuilder::new();
+        let mut is_empty = true;
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                if let Some(color) = tile.kind.overlay_color() {
+                    builder.rectangle(graphics::DrawMode::fill(), self.cell_rect(row, col), color);
+                    is_empty = false;
+                }
+            }
+        }
+        // building an empty mesh fails
+        if is_empty {
+            return;
+        }
+
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing Board");
+    }
+
+    /// Check if every tile that is not a wall can be reached from any other one
+    ///
+    pub fn is_solvable(&self) -> bool {
+        let is_open = |row: usize, col: usize| self.tiles[row][col].kind != TileKind::Wall;
+        let open: Vec<_> = self
+            .tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(row, tiles)| (0..tiles.len()).map(move |col| (row, col)))
+            .filter(|&(row, col)| is_open(row, col))
+            .collect();
+        let start = match open.first() {
+            Some(&start) => start,
+            None => return true,
+        };
+
+        let mut visited = vec![vec![false; self.tiles[0].len()]; self.tiles.len()];
+        visited[start.0][start.1] = true;
+        let mut queue = VecDeque::from(vec![start]);
+        let mut reached = 0;
+        while let Some((row, col)) = queue.pop_front() {
+            reached += 1;
+            let neighbours = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ];
+            for &(row, col) in &neighbours {
+                if row < self.tiles.len()
+                    && col < self.tiles[row].len()
+                    && !visited[row][col]
+                    && is_open(row, col)
+                {
+                    visited[row][col] = true;
+                    queue.push_back((row, col));
+                }
+            }
+        }
+
+        reached == open.len()
+    }
+
+    fn cell_rect(&self, row: usize, col: usize) -> Rect {
+        Rect::new(
+            col as f32 * self.cell_size,
+            row as f32 * self.cell_size,
+            self.cell_size,
+            self.cell_size,
+        )
+    }
+}
+
+/// Generate a maze of `width` x `height` tiles, each one `consts::MAZE_CELL_SIZE` wide,
+/// with the recursive backtracking algorithm.
+///
+/// Passages lie on the odd rows and columns, everything else starts as a wall.
+/// Walls between neighbouring passages are carved out in a random order,
+/// given by `seed`, so every open tile can be reached from every other one.
+///
+pub fn generate_maze(width: usize, height: usize, seed: u64) -> Board {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut board = Board {
+        tiles: vec![
+            vec![
+                Tile {
+                    kind: TileKind::Wall
+                };
+                width
+            ];
+            height
+        ],
+        cell_size: consts::MAZE_CELL_SIZE,
+    };
+    if width < 3 || height < 3 {
+        return board;
+    }
+
+    // backtracking is done with an explicit stack, so big mazes cannot overflow the call stack
+    board.tiles[1][1].kind = TileKind::Normal;
+    let mut stack = vec![(1, 1)];
+    while let Some(&(row, col)) = stack.last() {
+        let unvisited: Vec<(usize, usize)> = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .iter()
+            .map(|&(d_row, d_col)| (row as i32 + d_row, col as i32 + d_col))
+            .filter(|&(row, col)| {
+                row > 0 && col > 0 && row < height as i32 - 1 && col < width as i32 - 1
+            })
+            .map(|(row, col)| (row as usize, col as usize))
+            .filter(|&(row, col)| board.tiles[row][col].kind == TileKind::Wall)
+            .collect();
+
+        match unvisited.choose(&mut rng) {
+            Some(&(next_row, next_col)) => {
+                board.tiles[(row + next_row) / 2][(col + next_col) / 2].kind = TileKind::Normal;
+                board.tiles[next_row][next_col].kind = TileKind::Normal;
+                stack.push((next_row, next_col));
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    debug_assert!(
+        board.is_solvable(),
+        "Generated maze has unreachable passages"
+    );
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_mazes_are_solvable() {
+        for seed in 0..50 {
+            let board = generate_maze(13, 13, seed);
+            assert!(board.is_solvable(), "maze of seed {} is not solvable", seed);
+            assert_eq!(board.tiles[1][1].kind, TileKind::Normal);
+        }
+    }
+
+    #[test]
+    fn is_solvable_detects_walled_off_tiles() {
+        let mut board = Board::new(Coords::new(5., 5.), 1.);
+        assert!(board.is_solvable());
+        for col in 0..5 {
+            board.tiles[2][col].kind = TileKind::Wall;
+        }
+        assert!(!board.is_solvable());
+    }
+}
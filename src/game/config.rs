@@ -0,0 +1,518 @@
+//! Module with GameConfig struct, gathering parameters that can be changed without recompiling
+use clap::Parser;
+use ggez::{event::KeyCode, GameError, GameResult};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::game::{consts, difficulty::DifficultyLevel, direction::Direction, theme::ThemeName};
+
+/// Options given on the command line when starting the game
+///
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Snake game with smooth turns")]
+pub struct StartupArgs {
+    /// Seed of the food placement, the same seed gives the same game
+    #[arg(long, env = "SNAEK_SEED")]
+    pub seed: Option<u64>,
+
+    /// Difficulty level: easy, normal, hard or insane, overrides the one from the config
+    #[arg(long)]
+    pub difficulty: Option<DifficultyLevel>,
+
+    /// Game mode: classic, timed, survival, autoplay, shrinking, maze, daily or speedrun
+    #[arg(long, default_value = "classic")]
+    pub mode: String,
+
+    /// Two players on a shared screen, the second one steers with WASD
+    #[arg(long)]
+    pub two_player: bool,
+
+    /// Path to the TOML config file
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Start the game in fullscreen
+    #[arg(long, conflicts_with = "headless")]
+    pub fullscreen: bool,
+
+    /// Play the given number of games without a window, steered by the AI,
+    /// and print their summary
+    #[arg(long, value_name = "N")]
+    pub headless: Option<u32>,
+
+    /// Play a fixed set of AI-steered games without a window and print the throughput
+    #[arg(long, conflicts_with_all = ["headless", "fullscreen", "replay"])]
+    pub benchmark: bool,
+
+    /// Path to the replay file to play back
+    #[arg(long, conflicts_with = "headless")]
+    pub replay: Option<PathBuf>,
+}
+
+/// Enumeration of the ways the snake can be steered from the keyboard
+///
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum ControlScheme {
+    Arrows,
+    Wasd,
+    Custom,
+}
+
+impl ControlScheme {
+    /// Returns all schemes, in the order they are cycled through
+    ///
+    pub fn all() -> [ControlScheme; 3] {
+        [
+            ControlScheme::Arrows,
+            ControlScheme::Wasd,
+            ControlScheme::Custom,
+        ]
+    }
+}
+
+impl fmt::Display for ControlScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ControlScheme::Arrows => "Arrows",
+            ControlScheme::Wasd => "WASD",
+            ControlScheme::Custom => "Custom",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Names of the keys bound to each action, as read from the config file
+///
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct KeyConfig {
+    pub up: Vec<String>,
+    pub down: Vec<String>,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub start: Vec<String>,
+    pub pause: Vec<String>,
+    pub reset: Vec<String>,
+    pub restart: Vec<String>,
+    pub difficulty: Vec<String>,
+    pub wrap_walls: Vec<String>,
+    pub record: Vec<String>,
+    pub replay: Vec<String>,
+    pub autoplay: Vec<String>,
+    pub two_player: Vec<String>,
+    pub player_two_up: Vec<String>,
+    pub player_two_down: Vec<String>,
+    pub player_two_left: Vec<String>,
+    pub player_two_right: Vec<String>,
+    pub fullscreen: Vec<String>,
+    pub select: Vec<String>,
+    pub mute: Vec<String>,
+    pub grid: Vec<String>,
+    pub screenshot: Vec<String>,
+    pub palette: Vec<String>,
+    pub export_palette: Vec<String>,
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let direction = |scheme: fn(KeyCode) -> Option<Direction>, dir| {
+            direction_keys(scheme, dir)
+                .into_iter()
+                .filter_map(keycode_name)
+                .map(str::to_owned)
+                .collect()
+        };
+        Self {
+            up: direction(Direction::from_keycode, Direction::Up),
+            down: direction(Direction::from_keycode, Direction::Down),
+            left: direction(Direction::from_keycode, Direction::Left),
+            right: direction(Direction::from_keycode, Direction::Right),
+            start: keys(&["Space"]),
+            pause: keys(&["Escape", "P"]),
+            reset: keys(&["Back"]),
+            restart: keys(&["R"]),
+            difficulty: keys(&["Tab"]),
+            wrap_walls: keys(&["B"]),
+            record: keys(&["V"]),
+            replay: keys(&["O"]),
+            autoplay: keys(&["I"]),
+            two_player: keys(&["2"]),
+            player_two_up: direction(Direction::from_keycode_wasd, Direction::Up),
+            player_two_down: direction(Direction::from_keycode_wasd, Direction::Down),
+            player_two_left: direction(Direction::from_keycode_wasd, Direction::Left),
+            player_two_right: direction(Direction::from_keycode_wasd, Direction::Right),
+            fullscreen: keys(&["F11"]),
+            select: keys(&["Return", "Space"]),
+            mute: keys(&["M"]),
+            grid: keys(&["G"]),
+            screenshot: keys(&["F12"]),
+            palette: keys(&["T"]),
+            export_palette: keys(&["E"]),
+        }
+    }
+}
+
+impl KeyConfig {
+    fn all(&self) -> Vec<&String> {
+        [
+            &self.up,
+            &self.down,
+            &self.left,
+            &self.right,
+            &self.start,
+            &self.pause,
+            &self.reset,
+            &self.restart,
+            &self.difficulty,
+            &self.wrap_walls,
+            &self.record,
+            &self.replay,
+            &self.autoplay,
+            &self.two_player,
+            &self.player_two_up,
+            &self.player_two_down,
+            &self.player_two_left,
+            &self.player_two_right,
+            &self.fullscreen,
+            &self.select,
+            &self.mute,
+            &self.grid,
+            &self.screenshot,
+            &self.palette,
+            &self.export_palette,
+        ]
+        .iter()
+        .flat_map(|names| names.iter())
+        .collect()
+    }
+}
+
+/// Structure gathering all tunable game parameters.
+///
+/// Every field is optional in the TOML file, missing ones get their default value.
+/// Snake and food dimensions stay in `consts`, as segment shapes are computed from them.
+/// Settings changed in the game are written back with `save`.
+///
+/// Hunger is off by default, as `hunger_rate` is infinite.
+/// Set it to a number of seconds to make the snake shrink when it does not eat for that long.
+///
+/// With `cache_static_layer` the background and the terrain are drawn once into a canvas,
+/// which is reused until they change. Everything else is still redrawn every frame.
+/// The old `optimize_rendering` name is still read from config files.
+///
+/// With `rainbow_snake` the snake body is colored with all hues of the rainbow,
+/// which slowly rotate along it, instead of the theme gradient.
+///
+/// `lives` is the number of deaths a run lasts, after each but the last the snake respawns
+/// in the middle of the screen. With 0 or 1 the first death ends the run.
+///
+/// `font_path` points to a TTF file used for all texts instead of the bundled font.
+/// `language` names the file in `consts::STRINGS_DIR` the texts are read from.
+///
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct GameConfig {
+    pub screen_width: f32,
+    pub screen_height: f32,
+    pub snake_speed: f32,
+    pub secs_per_input_update: f32,
+    pub max_food: usize,
+    pub max_input_queue: usize,
+    pub food_max_age: f32,
+    pub timed_duration: f32,
+    pub speedrun_target: u32,
+    pub lives: u32,
+    pub hunger_rate: f32,
+    pub hunger_shrink: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub theme: ThemeName,
+    pub control_scheme: ControlScheme,
+    pub difficulty: DifficultyLevel,
+    #[serde(alias = "optimize_rendering")]
+    pub cache_static_layer: bool,
+    pub rainbow_snake: bool,
+    pub font_path: Option<PathBuf>,
+    pub language: String,
+    // tables have to be written after all plain values in TOML
+    pub keys: KeyConfig,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            screen_width: consts::SCREEN_SIZE.x,
+            screen_height: consts::SCREEN_SIZE.y,
+            snake_speed: consts::SPEED,
+            secs_per_input_update: consts::SECS_PER_INPUT_UPDATE,
+            max_food: consts::MAX_FOOD,
+            max_input_queue: consts::MAX_INPUT_QUEUE,
+            food_max_age: consts::FOOD_MAX_AGE,
+            timed_duration: consts::TIMED_DURATION,
+            speedrun_target: consts::SPEEDRUN_TARGET,
+            lives: consts::LIVES,
+            hunger_rate: f32::INFINITY,
+            hunger_shrink: consts::HUNGER_SHRINK,
+            master_volume: 1.,
+            music_volume: 0.8,
+            sfx_volume: 0.8,
+            theme: ThemeName::Classic,
+            control_scheme: ControlScheme::Custom,
+            difficulty: DifficultyLevel::Normal,
+            cache_static_layer: false,
+            rainbow_snake: false,
+            font_path: None,
+            language: consts::DEFAULT_LANGUAGE.to_owned(),
+            keys: KeyConfig::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Read config from the given TOML file and validate it
+    ///
+    pub fn from_toml(path: &Path) -> GameResult<Self> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            GameError::ConfigError(format!("Could not read {}: {}", path.display(), err))
+        })?;
+        let config: Self = toml::from_str(&content).map_err(|err| {
+            GameError::ConfigError(format!("Malformed config {}: {}", path.display(), err))
+        })?;
+        config.validate().map_err(GameError::ConfigError)?;
+
+        Ok(config)
+    }
+
+    /// Write config into the given TOML file
+    ///
+    pub fn save(&self, path: &Path) -> GameResult {
+        let content =
+            toml::to_string(self).map_err(|err| GameError::ConfigError(err.to_string()))?;
+        fs::write(path, content).map_err(|err| {
+            GameError::ConfigError(format!("Could not write {}: {}", path.display(), err))
+        })
+    }
+
+    /// Check if all values are in their allowed ranges
+    ///
+    pub fn validate(&self) -> Result<(), String> {
+        let min_screen = consts::SNAKE_START_HEIGHT * 2.;
+        if self.screen_width < min_screen || self.screen_height < min_screen {
+            return Err(format!("Screen has to be at least {0}x{0}", min_screen));
+        }
+        if self.snake_speed <= 0. {
+            return Err("Snake speed has to be positive".to_string());
+        }
+        // two opposite turns need enough space between them, to not overlap
+        if self.secs_per_input_update * self.snake_speed < consts::SNAKE_WIDTH + consts::TURN_MARGIN
+        {
+            return Err(format!(
+                "Input update is too frequent for the snake speed, needs at least {} secs",
+                (consts::SNAKE_WIDTH + consts::TURN_MARGIN) / self.snake_speed
+            ));
+        }
+        if self.max_food == 0 {
+            return Err("There has to be at least one food item".to_string());
+        }
+        if self.max_input_queue == 0 {
+            return Err("Input queue has to hold at least one direction".to_string());
+        }
+        if self.food_max_age <= 0. {
+            return Err("Food max age has to be positive".to_string());
+        }
+        if self.timed_duration <= 0. {
+            return Err("Timed mode duration has to be positive".to_string());
+        }
+        if self.speedrun_target == 0 {
+            return Err("Speed-run target score has to be positive".to_string());
+        }
+        if self.hunger_rate <= 0. {
+            return Err("Hunger rate has to be positive".to_string());
+        }
+        if self.hunger_shrink < 0. {
+            return Err("Hunger shrink cannot be negative".to_string());
+        }
+        if [self.master_volume, self.music_volume, self.sfx_volume]
+            .iter()
+            .any(|volume| !(0. ..=1.).contains(volume))
+        {
+            return Err("Volume has to be between 0 and 1".to_string());
+        }
+        if let Some(name) = self
+            .keys
+            .all()
+            .into_iter()
+            .find(|name| parse_keycode(name).is_none())
+        {
+            return Err(format!("Unknown key name: {}", name));
+        }
+
+        Ok(())
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 57] = [
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Space", KeyCode::Space),
+    ("Escape", KeyCode::Escape),
+    ("Tab", KeyCode::Tab),
+    ("Back", KeyCode::Back),
+    ("Return", KeyCode::Return),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+];
+
+/// Parse a key name, as used in the config file, into `KeyCode`
+///
+pub fn parse_keycode(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|&&(key_name, _)| key_name == name)
+        .map(|&(_, keycode)| keycode)
+}
+
+/// Return the name of the key, as used in the config file, the inverse of `parse_keycode`
+///
+pub fn keycode_name(keycode: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|&&(_, key)| key == keycode)
+        .map(|&(name, _)| name)
+}
+
+/// Return the keys that `scheme`, one of the `Direction::from_keycode` variants, maps to `dir`.
+/// Arrow keys come first, so they are the ones shown in the key hints.
+///
+pub fn direction_keys(scheme: fn(KeyCode) -> Option<Direction>, dir: Direction) -> Vec<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .map(|&(_, keycode)| keycode)
+        .filter(|&keycode| scheme(keycode) == Some(dir))
+        .sorted_by_key(|&keycode| Direction::from_keycode_arrows(keycode).is_none())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+
+    #[test]
+    fn headless_takes_the_number_of_games() {
+        let args = StartupArgs::try_parse_from(["snaek_rust", "--headless", "5"]).unwrap();
+        assert_eq!(args.headless, Some(5));
+        assert!(!args.fullscreen);
+    }
+
+    #[test]
+    fn headless_cannot_be_combined_with_a_window() {
+        let fullscreen =
+            StartupArgs::try_parse_from(["snaek_rust", "--headless", "5", "--fullscreen"]);
+        assert_eq!(fullscreen.unwrap_err().kind(), ErrorKind::ArgumentConflict);
+
+        let replay =
+            StartupArgs::try_parse_from(["snaek_rust", "--headless", "5", "--replay", "a.replay"]);
+        assert_eq!(replay.unwrap_err().kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn old_optimize_rendering_name_is_still_read() {
+        let config: GameConfig = toml::from_str("optimize_rendering = true").unwrap();
+        assert!(config.cache_static_layer);
+    }
+
+    #[test]
+    fn default_movement_keys_list_arrows_before_wasd() {
+        let keys = KeyConfig::default();
+        assert_eq!(keys.up, ["Up", "W"]);
+        assert_eq!(keys.down, ["Down", "S"]);
+        assert_eq!(keys.left, ["Left", "A"]);
+        assert_eq!(keys.right, ["Right", "D"]);
+        assert_eq!(keys.player_two_up, ["W"]);
+    }
+
+    #[test]
+    fn default_pregame_keys_do_not_move_either_snake() {
+        let keys = KeyConfig::default();
+        let movement = [
+            &keys.up,
+            &keys.down,
+            &keys.left,
+            &keys.right,
+            &keys.player_two_up,
+            &keys.player_two_down,
+            &keys.player_two_left,
+            &keys.player_two_right,
+        ];
+        let pregame = [
+            &keys.difficulty,
+            &keys.wrap_walls,
+            &keys.record,
+            &keys.replay,
+            &keys.autoplay,
+            &keys.two_player,
+        ];
+        for name in pregame.iter().flat_map(|names| names.iter()) {
+            assert!(
+                movement.iter().all(|names| !names.contains(name)),
+                "{} both moves and toggles",
+                name
+            );
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Module that gathers all more or less modifiable parameters
 use crate::game::coords::Coords;
+use ggez::graphics::Rect;
 
 pub const GAME_ID: &str = "snaek_rust";
 pub const GAME_AUTHOR: &str = "mk.kulagowski";
@@ -11,20 +12,164 @@ pub const SPEED: f32 = SNAKE_WIDTH * 15.;
 
 pub const WALL_MARGIN: f32 = SNAKE_WIDTH * 0.5;
 pub const COLLISION_PIXELS_MARGIN: f32 = 1.;
+pub const POINT_EPSILON: f32 = 0.001;
 pub const FOOD_SIZE: f32 = SNAKE_WIDTH;
 pub const FOOD_HALF_SIZE: f32 = FOOD_SIZE / 2.;
+pub const MAX_FOOD: usize = 3;
+pub const MAX_INPUT_QUEUE: usize = 4;
+pub const INPUT_SQUARE_SIZE: f32 = 8.;
+pub const FOOD_MAX_AGE: f32 = 10.;
+pub const FOOD_SPAWN_SECS: f32 = 0.3;
+pub const FOOD_MIN_HEAD_DISTANCE: f32 = SNAKE_WIDTH * 3.;
+pub const FOOD_SPAWN_RETRIES: usize = 1000;
+pub const FOOD_TIME_BONUS: f32 = 5.;
+pub const BIG_BITE_SCORE: u32 = 20;
+pub const COMBO_SECS: f32 = 5.;
+pub const TIMED_DURATION: f32 = 60.;
+pub const SPEEDRUN_TARGET: u32 = 100;
+pub const SPEEDRUN_RECORDS: usize = 5;
+pub const LIVES: u32 = 3;
+pub const RESPAWN_SECS: f32 = 2.;
+pub const RESPAWN_FLASH_FREQ: f32 = 8.;
+pub const HEART_SIZE: f32 = 16.;
+pub const TIMED_FOOD_BONUS_SECS: f32 = 2.;
+pub const TIMED_WARNING_SECS: f32 = 10.;
+pub const SPEED_RAMP_PERIOD: f32 = 60.;
+pub const SURVIVAL_GROW_SECS: f32 = 15.;
+pub const SURVIVAL_GROW_LEN: f32 = SNAKE_WIDTH;
+pub const SHRINK_BORDER_SECS: f32 = 30.;
+pub const SHRINK_MIN_SAFE_ZONE: f32 = SNAKE_WIDTH * 10.;
+pub const MAZE_CELL_SIZE: f32 = SNAKE_WIDTH * 3.;
+pub const SPATIAL_CELL_SIZE: f32 = SNAKE_WIDTH * 4.;
+pub const COMBO_FLASH_MIN: u32 = 5;
+pub const HUNGER_SHRINK: f32 = SNAKE_WIDTH;
+pub const HUNGER_BAR_SIZE: Coords = Coords { x: 200.0, y: 10.0 };
+pub const PULSE_FREQ: f32 = 6.;
+pub const PULSE_SCALE: f32 = 0.2;
+pub const DANGER_PULSE_FREQ: f32 = 12.;
+pub const DANGER_BORDER_WIDTH: f32 = 6.;
+pub const SPEED_BOOST_SECS: f32 = 5.;
+pub const SPEED_BOOST_MULT: f32 = 1.5;
+pub const SHRINK_LEN: f32 = SNAKE_WIDTH * 3.;
+pub const SNAKE_MIN_LEN: f32 = SNAKE_WIDTH * 2.;
+pub const SCORE_DOUBLE_SECS: f32 = 10.;
+pub const GHOST_SECS: f32 = 5.;
+pub const SHIELD_SECS: f32 = 30.;
+pub const SHIELD_GRACE_SECS: f32 = 0.5;
+pub const CONFUSION_SECS: f32 = 5.;
+pub const FREEZE_SECS: f32 = 3.;
+pub const MAGNET_SECS: f32 = 8.;
+pub const MAGNET_SPEED: f32 = SNAKE_WIDTH * 6.;
+pub const MAGNET_IMPULSE: f32 = SNAKE_WIDTH * 2.;
+pub const SHIELD_RADIUS: f32 = HEAD_HALF_WIDTH * 1.6;
 pub const SNAKE_HALF_WIDTH: f32 = SNAKE_WIDTH / 2.;
 pub const SNAKE_START_HEIGHT: f32 = SNAKE_WIDTH * SNAKE_START_LEN as f32;
 pub const TURN_MARGIN: f32 = SNAKE_WIDTH * 0.15;
 pub const HALF_TURN_MARGIN: f32 = TURN_MARGIN / 2.;
+pub const HEAD_HALF_WIDTH: f32 = SNAKE_HALF_WIDTH * 1.2;
+pub const GLOW_PASSES: [(f32, f32); 2] = [(2., 0.5), (5., 0.2)];
+pub const EYE_RADIUS: f32 = 3.;
+pub const PUPIL_RADIUS: f32 = 1.5;
+pub const PARTICLE_SPEED: f32 = SNAKE_WIDTH * 8.;
+pub const PARTICLE_LIFE_SECS: f32 = 0.8;
+pub const PARTICLE_RADIUS: f32 = 3.;
+pub const EAT_PARTICLES: usize = 16;
+pub const HUNGER_PARTICLES: usize = 8;
+pub const PARTICLE_SPRAY_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+pub const DEATH_PARTICLES: usize = 48;
+pub const DEATH_PARTICLES_PER_CELL: f32 = 2.;
+pub const SHAKE_SECS: f32 = 0.3;
+pub const SHAKE_MAGNITUDE: f32 = 5.;
+pub const POPUP_SECS: f32 = 1.;
+pub const POPUP_DRIFT: f32 = SNAKE_WIDTH * 2.;
+pub const NOTICE_SECS: f32 = 2.;
+pub const RAINBOW_ROTATION_SECS: f32 = 10.;
+pub const Z_WORLD: i32 = 0;
+pub const Z_ITEMS: i32 = 1;
+pub const Z_PARTICLES: i32 = 5;
+pub const Z_POPUPS: i32 = 10;
+pub const STICK_DEAD_ZONE: f32 = 0.5;
 pub const SECS_PER_INPUT_UPDATE: f32 = (SNAKE_WIDTH + TURN_MARGIN) / SPEED;
+pub const SECS_PER_PHYSICS_STEP: f32 = 1. / 60.;
+pub const HEADLESS_MAX_SECS: f32 = 600.;
+pub const BENCHMARK_GAMES: u32 = 10_000;
+pub const MAX_FRAME_SECS: f32 = 0.25;
+pub const DEBUG_GRAPH_SAMPLES: usize = 60;
+pub const DEBUG_GRAPH_SIZE: Coords = Coords { x: 120.0, y: 40.0 };
+pub const DEBUG_GRAPH_MAX_SECS: f32 = 1. / 30.;
 
-pub const PREGAME_TXT: &str = "Press SPACE to start the game";
+pub const DEFAULT_OBSTACLES: &[Rect] = &[];
+pub const OBSTACLE_NAME: &str = "wall";
+pub const MAZE_WALL_NAME: &str = "maze wall";
+pub const LEVEL_SCORE_STEP: u32 = 200;
+pub const LEVEL_CLEAR_SECS: f32 = 2.;
+pub const PORTAL_RINGS: usize = 4;
+pub const ICE_SLIDE_STEPS: u32 = 2;
+pub const PORTAL_SWIRL_FREQ: f32 = 4.;
+
+pub const CONFUSION_TXT: &str = "!";
+pub const VOLUME_STEP: f32 = 0.1;
+pub const CROSSFADE_SECS: f32 = 0.5;
+pub const NEW_RECORD_FLASH_SECS: f32 = 3.;
+
+pub const CONFIG_FILE: &str = "./config.toml";
+pub const HIGHSCORE_FILE: &str = "highscore.json";
+pub const ACHIEVEMENTS_FILE: &str = "achievements.json";
+pub const STATS_FILE: &str = "stats.json";
+pub const DAILY_FILE: &str = "daily.json";
+pub const SPEEDRUN_FILE: &str = "speedruns.json";
+pub const LAST_REPLAY_FILE: &str = "last.replay";
+pub const SAVE_FILE: &str = "save.bin";
+pub const PALETTE_EXPORT_FILE: &str = "palette.json";
+pub const LEVELS_DIR: &str = "./resources/levels";
+pub const PALETTES_DIR: &str = "./resources/palettes";
+pub const STRINGS_DIR: &str = "./resources/strings";
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Cannot use const value here, as macro requires literals
+#[macro_export]
+macro_rules! SETTING_FMT {
+    () => {
+        "{}: < {} >"
+    };
+}
+
+/// Cannot use const value here, as macro requires literals
+#[macro_export]
+macro_rules! SCREENSHOT_FILE_FMT {
+    () => {
+        "snaek_{}.png"
+    };
+}
+
+/// Cannot use const value here, as macro requires literals
+#[macro_export]
+macro_rules! HEADLESS_SUMMARY_FMT {
+    () => {
+        "games: {}  average score: {:.2}  max score: {}  games per second: {:.2}"
+    };
+}
+
+/// Cannot use const value here, as macro requires literals
+#[macro_export]
+macro_rules! BENCHMARK_FMT {
+    () => {
+        "games_per_second={:.3} mean_score={:.3} p99_score={}"
+    };
+}
+
+/// Cannot use const value here, as macro requires literals
+#[macro_export]
+macro_rules! DATE_FMT {
+    () => {
+        "{:04}-{:02}-{:02}"
+    };
+}
 
 /// Cannot use const value here, as macro requires literals
 #[macro_export]
-macro_rules! SCORE_FMT {
+macro_rules! SPEEDRUN_TIME_FMT {
     () => {
-        "Score: {}"
+        "{:02}:{:02}.{:03}"
     };
 }
@@ -1,10 +1,14 @@
 //! Module with Coords struct
-use rand::distributions::{Distribution, Uniform};
-use std::ops::{Add, AddAssign, Mul, Sub};
+use rand::{
+    distributions::{Distribution, Uniform},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
 /// Structure for holding 2D coordinates
 ///
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct Coords {
     pub x: f32,
     pub y: f32,
@@ -17,17 +21,82 @@ impl Coords {
         Self { x, y }
     }
 
-    /// Construct a random Coords struct with both coordinates between min and max
+    /// Construct a random Coords struct with each coordinate between the ones of min and max,
+    /// using the given random number generator
     ///
-    pub fn random(min: f32, max: f32) -> Self {
-        let mut rng = rand::thread_rng();
-        let xrand = Uniform::from(min..max);
-        let yrand = Uniform::from(min..max);
+    pub fn random_with(rng: &mut impl Rng, min: Coords, max: Coords) -> Self {
+        let xrand = Uniform::from(min.x..max.x);
+        let yrand = Uniform::from(min.y..max.y);
         Self {
-            x: xrand.sample(&mut rng),
-            y: yrand.sample(&mut rng),
+            x: xrand.sample(rng),
+            y: yrand.sample(rng),
         }
     }
+
+    /// Linear interpolation towards `other`, where `t` of 0 gives `self` and 1 gives `other`.
+    /// `t` is clamped to that range.
+    ///
+    pub fn lerp(self, other: Coords, t: f32) -> Coords {
+        self + (other - self) * f32::clamp(t, 0., 1.)
+    }
+
+    /// Return length of the vector
+    ///
+    pub fn magnitude(&self) -> f32 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    /// Return vector of the same direction and length of 1,
+    /// or zero vector when the length is 0
+    ///
+    pub fn normalize(&self) -> Coords {
+        let magnitude = self.magnitude();
+        if magnitude == 0. {
+            Coords::new(0., 0.)
+        } else {
+            *self / magnitude
+        }
+    }
+
+    /// Return euclidean distance between `self` and `other`
+    ///
+    pub fn distance(&self, other: &Coords) -> f32 {
+        (*self - *other).magnitude()
+    }
+
+    /// Return the point moved inside the area from (0, 0) to `bounds`.
+    /// Negative bounds are treated as 0.
+    ///
+    pub fn clamp_to_bounds(&self, bounds: Coords) -> Coords {
+        Coords::new(
+            f32::clamp(self.x, 0., f32::max(bounds.x, 0.)),
+            f32::clamp(self.y, 0., f32::max(bounds.y, 0.)),
+        )
+    }
+
+    /// Return the point rounded to the nearest multiple of `cell_size` on both axes
+    ///
+    pub fn snap_to_grid(&self, cell_size: f32) -> Coords {
+        Coords::new(
+            (self.x / cell_size).round() * cell_size,
+            (self.y / cell_size).round() * cell_size,
+        )
+    }
+
+    /// Return the column and the row of the grid cell of `cell_size` that the point lies in
+    ///
+    pub fn grid_cell(&self, cell_size: f32) -> (i32, i32) {
+        (
+            (self.x / cell_size).floor() as i32,
+            (self.y / cell_size).floor() as i32,
+        )
+    }
+
+    /// Return scalar product of `self` and `other`
+    ///
+    pub fn dot(&self, other: &Coords) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
 }
 
 impl AddAssign for Coords {
@@ -60,6 +129,26 @@ impl Sub for Coords {
     }
 }
 
+impl SubAssign for Coords {
+    fn sub_assign(&mut self, other: Self) {
+        *self = Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        };
+    }
+}
+
+impl Neg for Coords {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
 impl Mul for Coords {
     type Output = Self;
 
@@ -93,11 +182,22 @@ impl Mul<Coords> for f32 {
     }
 }
 
-impl Into<ggez::mint::Point2<f32>> for Coords {
-    fn into(self) -> ggez::mint::Point2<f32> {
+impl Div<f32> for Coords {
+    type Output = Self;
+
+    fn div(self, other: f32) -> Self::Output {
+        Self::Output {
+            x: self.x / other,
+            y: self.y / other,
+        }
+    }
+}
+
+impl From<Coords> for ggez::mint::Point2<f32> {
+    fn from(coords: Coords) -> Self {
         ggez::mint::Point2 {
-            x: self.x,
-            y: self.y,
+            x: coords.x,
+            y: coords.y,
         }
     }
 }
@@ -0,0 +1,104 @@
+//! Module with the date helpers and DailyHighScore struct of the daily challenge
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::game::consts;
+
+/// Calendar date as year, month and day, both counted from 1
+///
+pub type Date = (i32, u32, u32);
+
+/// Return the current date in UTC, so players in all time zones share the same day
+///
+pub fn today_utc() -> Date {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    date_from_days((secs / 86_400) as i64)
+}
+
+/// Return the seed of the daily challenge played on `date`
+///
+pub fn seed_of((year, month, day): Date) -> u64 {
+    year as u64 * 10_000 + month as u64 * 100 + day as u64
+}
+
+/// Convert the number of days since 1970-01-01 into a date of the Gregorian calendar.
+/// Years are counted from March, so the leap day falls at their end.
+///
+fn date_from_days(days: i64) -> Date {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month as u32, day as u32)
+}
+
+/// Structure with the best score of a single daily challenge.
+///
+/// Score is kept as a JSON file next to the all-time high score file.
+/// Only the latest challenge is stored, so the best score starts over every day.
+///
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy, Debug)]
+pub struct DailyHighScore {
+    pub seed: u64,
+    pub best: u32,
+}
+
+impl DailyHighScore {
+    /// Read the best score of the challenge with the given seed.
+    /// Missing or malformed file, or a file from another day, counts as 0.
+    ///
+    pub fn load(seed: u64) -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|stored| stored.seed == seed)
+            .unwrap_or(Self { seed, best: 0 })
+    }
+
+    /// Store `score` as the new best score, if it beats the current one.
+    /// Returns whether it did.
+    ///
+    pub fn record(&mut self, score: u32) -> bool {
+        if score <= self.best {
+            return false;
+        }
+        self.best = score;
+
+        if let Some(path) = Self::path() {
+            let content =
+                serde_json::to_string(self).expect("Error while serializing daily high score");
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, content));
+            if let Err(err) = written {
+                eprintln!(
+                    "Could not save daily high score to {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        true
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::DAILY_FILE))
+    }
+}
@@ -0,0 +1,75 @@
+//! Module with DangerLevel enum, telling how close the snake is to crashing
+use ggez::graphics::Color;
+
+use crate::game::{consts, coords::Coords, snake::Snake};
+
+/// Enumeration of how close the head is to a wall or to the snake body
+///
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum DangerLevel {
+    /// Nothing deadly within 2 snake widths
+    Safe,
+    /// Something deadly within 2 snake widths
+    Caution,
+    /// Something deadly within 1 snake width
+    Critical,
+}
+
+impl DangerLevel {
+    /// Return the level for the given distance from the head to the nearest danger
+    ///
+    pub fn from_distance(dist: f32) -> Self {
+        if dist <= consts::SNAKE_WIDTH {
+            DangerLevel::Critical
+        } else if dist <= consts::SNAKE_WIDTH * 2. {
+            DangerLevel::Caution
+        } else {
+            DangerLevel::Safe
+        }
+    }
+
+    /// Return the level of `snake` on a screen of size `screen`.
+    ///
+    /// Walls count only when `walls` is set, shrunk by the hazard `margin`.
+    /// The body counts by the middle points of its segments,
+    /// without the two segments next to the head, which always lie close to it.
+    ///
+    pub fn of(snake: &Snake, screen: Coords, walls: bool, margin: f32) -> Self {
+        let head = snake.head_position();
+        let wall_dist = if walls {
+            [
+                head.x - margin,
+                screen.x - margin - head.x,
+                head.y - margin,
+                screen.y - margin - head.y,
+            ]
+            .iter()
+            .copied()
+            .fold(f32::INFINITY, f32::min)
+        } else {
+            f32::INFINITY
+        };
+        let body_dist = snake
+            .body
+            .iter()
+            .skip(2)
+            .map(|segment| segment.beg().lerp(segment.end(), 0.5).distance(&head))
+            .fold(f32::INFINITY, f32::min);
+
+        Self::from_distance(f32::min(wall_dist, body_dist))
+    }
+
+    /// Return the color of the screen border for this level at `time` seconds,
+    /// or `None` when there is no border to draw
+    ///
+    pub fn border_color(&self, time: f32) -> Option<Color> {
+        match self {
+            DangerLevel::Safe => None,
+            DangerLevel::Caution => Some(Color::new(1., 0.85, 0.1, 0.6)),
+            DangerLevel::Critical => {
+                let pulse = 0.5 + 0.5 * (time * consts::DANGER_PULSE_FREQ).sin();
+                Some(Color::new(1., 0.1, 0.1, 0.3 + 0.5 * pulse))
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Module with DeathCause enum
+/// Enumeration of the ways a run can end
+///
+#[derive(PartialEq, Clone, Debug)]
+pub enum DeathCause {
+    /// Snake left the screen or entered the hazard border
+    Wall,
+    /// Snake bit its own body
+    SelfCollision,
+    /// Snake crashed into an obstacle with the given name
+    Obstacle(String),
+    /// Snake fell into the void
+    Void,
+    /// Snake crashed into the other player snake
+    OtherSnake,
+    /// Snake starved down to its minimal length
+    Hunger,
+    /// Time limit of the mode has run out
+    TimeUp,
+}
@@ -0,0 +1,111 @@
+//! Module with debug build helpers
+//!
+//! Helpers are used only with the `debug` feature enabled, so dead code is allowed otherwise.
+#![cfg_attr(not(feature = "debug"), allow(dead_code))]
+use ggez::{
+    graphics::{self, Color, DrawMode, MeshBuilder, Rect, Text, TextFragment},
+    Context,
+};
+use std::collections::VecDeque;
+
+use crate::game::{consts, coords::Coords, renderer::Renderer, theme::ColorTheme};
+
+/// Structure for collecting frame times and showing them as FPS and a bar chart
+///
+pub struct FrameStats {
+    pub samples: VecDeque<f32>,
+    pub max_samples: usize,
+}
+
+impl FrameStats {
+    /// Create new `FrameStats` keeping up to `max_samples` latest frame times
+    ///
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+        }
+    }
+
+    /// Store the duration of the last frame, dropping the oldest one when full
+    ///
+    pub fn push(&mut self, dt: f32) {
+        if self.samples.len() == self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(dt);
+    }
+
+    /// Return frames per second, averaged over all stored frame times
+    ///
+    pub fn fps(&self) -> f32 {
+        let total: f32 = self.samples.iter().sum();
+        if total <= 0. {
+            return 0.;
+        }
+
+        self.samples.len() as f32 / total
+    }
+
+    /// Draws the FPS readout and, below it, a bar for each stored frame time.
+    /// `(x, y)` is the top left corner of the readout.
+    ///
+    /// Bars are scaled so a frame of `consts::DEBUG_GRAPH_MAX_SECS` fills the whole graph height.
+    ///
+    pub fn draw_graph(&self, ctx: &mut Context, x: f32, y: f32, theme: &ColorTheme) {
+        let fps_txt = Text::new(
+            TextFragment::new(format!("FPS: {:.0}", self.fps()))
+                .scale(graphics::Scale::uniform(16.)),
+        );
+        Renderer::draw_text_with_outline(ctx, &fps_txt, Coords::new(x, y), theme);
+
+        let top = y + fps_txt.height(ctx) as f32 + 4.;
+        let (width, height) = (consts::DEBUG_GRAPH_SIZE.x, consts::DEBUG_GRAPH_SIZE.y);
+        let bar_width = width / self.max_samples as f32;
+
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(
+            DrawMode::fill(),
+            Rect::new(x, top, width, height),
+            Color::new(0., 0., 0., 0.5),
+        );
+        for (idx, dt) in self.samples.iter().enumerate() {
+            let bar_height = f32::min(dt / consts::DEBUG_GRAPH_MAX_SECS, 1.) * height;
+            builder.rectangle(
+                DrawMode::fill(),
+                Rect::new(
+                    x + idx as f32 * bar_width,
+                    top + height - bar_height,
+                    bar_width,
+                    bar_height,
+                ),
+                Color::from_rgb(0, 255, 0),
+            );
+        }
+
+        if let Ok(mesh) = builder.build(ctx) {
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+                .expect("Error while drawing frame graph");
+        }
+    }
+}
+
+/// Draws the move and turn counters of the current run, with their top left corner on `pos`
+///
+pub fn draw_counters(ctx: &mut Context, pos: Coords, moves: u64, turns: u64, theme: &ColorTheme) {
+    let txt = Text::new(
+        TextFragment::new(format!("Moves: {}\nTurns: {}", moves, turns))
+            .scale(graphics::Scale::uniform(16.)),
+    );
+    Renderer::draw_text_with_outline(ctx, &txt, pos, theme);
+}
+
+/// Create `FrameStats` in debug builds, other builds do not show them
+///
+pub fn create_frame_stats() -> Option<FrameStats> {
+    if cfg!(feature = "debug") {
+        Some(FrameStats::new(consts::DEBUG_GRAPH_SAMPLES))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,77 @@
+//! Module with DifficultyLevel enum
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Enumeration of available difficulty levels, each one moves the snake with a different speed
+///
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DifficultyLevel {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl DifficultyLevel {
+    /// Return value by which `GameConfig::snake_speed` is multiplied
+    ///
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            DifficultyLevel::Easy => 0.6,
+            DifficultyLevel::Normal => 1.,
+            DifficultyLevel::Hard => 1.5,
+            DifficultyLevel::Insane => 2.2,
+        }
+    }
+
+    /// Return all difficulty levels, from the easiest one
+    ///
+    pub fn all() -> [DifficultyLevel; 4] {
+        [
+            DifficultyLevel::Easy,
+            DifficultyLevel::Normal,
+            DifficultyLevel::Hard,
+            DifficultyLevel::Insane,
+        ]
+    }
+
+    /// Return the next difficulty level, wrapping around after the last one
+    ///
+    pub fn next(&self) -> Self {
+        match self {
+            DifficultyLevel::Easy => DifficultyLevel::Normal,
+            DifficultyLevel::Normal => DifficultyLevel::Hard,
+            DifficultyLevel::Hard => DifficultyLevel::Insane,
+            DifficultyLevel::Insane => DifficultyLevel::Easy,
+        }
+    }
+}
+
+impl fmt::Display for DifficultyLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DifficultyLevel::Easy => "Easy",
+            DifficultyLevel::Normal => "Normal",
+            DifficultyLevel::Hard => "Hard",
+            DifficultyLevel::Insane => "Insane",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for DifficultyLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(DifficultyLevel::Easy),
+            "normal" => Ok(DifficultyLevel::Normal),
+            "hard" => Ok(DifficultyLevel::Hard),
+            "insane" => Ok(DifficultyLevel::Insane),
+            _ => Err(format!(
+                "Unknown difficulty {}, expected easy, normal, hard or insane",
+                s
+            )),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use crate::game::coords::Coords;
+use ggez::event::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// Enumeration for 4 main directions
 ///
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Down,
@@ -15,9 +18,88 @@ impl Direction {
     /// i.e. if they are on the same axis (X/Y)
     ///
     pub fn is_colinear(&self, other: Self) -> bool {
+        self.is_horizontal() == other.is_horizontal()
+    }
+
+    /// Check if direction lies on the X axis
+    ///
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    /// Check if direction lies on the Y axis
+    ///
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+
+    /// Return the reversed direction, pointing the other way along the same axis.
+    /// Used for shoving the snake backwards and for the reversed controls of confusion.
+    ///
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// Return direction turned by 90 degrees clockwise
+    ///
+    pub fn rotate_cw(&self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    /// Return direction turned by 90 degrees counterclockwise
+    ///
+    pub fn rotate_ccw(&self) -> Self {
         match self {
-            Self::Up | Self::Down => matches!(other, Self::Up | Self::Down),
-            Self::Left | Self::Right => matches!(other, Self::Left | Self::Right),
+            Self::Up => Self::Left,
+            Self::Left => Self::Down,
+            Self::Down => Self::Right,
+            Self::Right => Self::Up,
+        }
+    }
+
+    /// Return all directions, for iteration
+    ///
+    pub fn all() -> [Self; 4] {
+        [Self::Up, Self::Down, Self::Left, Self::Right]
+    }
+
+    /// Map both arrow keys and WASD keys to directions
+    ///
+    pub fn from_keycode(kc: KeyCode) -> Option<Self> {
+        Self::from_keycode_arrows(kc).or_else(|| Self::from_keycode_wasd(kc))
+    }
+
+    /// Map WASD keys to directions
+    ///
+    pub fn from_keycode_wasd(kc: KeyCode) -> Option<Self> {
+        match kc {
+            KeyCode::W => Some(Self::Up),
+            KeyCode::S => Some(Self::Down),
+            KeyCode::A => Some(Self::Left),
+            KeyCode::D => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    /// Map arrow keys to directions
+    ///
+    pub fn from_keycode_arrows(kc: KeyCode) -> Option<Self> {
+        match kc {
+            KeyCode::Up => Some(Self::Up),
+            KeyCode::Down => Some(Self::Down),
+            KeyCode::Left => Some(Self::Left),
+            KeyCode::Right => Some(Self::Right),
+            _ => None,
         }
     }
 
@@ -33,3 +115,15 @@ impl Direction {
         Coords { x, y }
     }
 }
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Direction::Up => "Up",
+            Direction::Down => "Down",
+            Direction::Left => "Left",
+            Direction::Right => "Right",
+        };
+        write!(f, "{}", name)
+    }
+}
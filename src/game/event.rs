@@ -0,0 +1,44 @@
+//! Module with GameEvent enum and the trait for reacting to it
+use ggez::graphics::Color;
+
+use crate::game::{coords::Coords, death::DeathCause, food::FoodKind};
+
+/// Enumeration of the things that happen during a physics step,
+/// which other subsystems may want to react to.
+///
+/// Events are queued while the snakes are updated
+/// and handed over to every `GameEventListener` once the step is over.
+///
+#[derive(PartialEq, Clone, Debug)]
+pub enum GameEvent {
+    /// Food of a given kind was eaten on `pos`, giving `score` points
+    FoodEaten {
+        pos: Coords,
+        kind: FoodKind,
+        score: u32,
+    },
+    /// Snake drawn with `color` died on `pos`
+    SnakeDied {
+        cause: DeathCause,
+        pos: Coords,
+        color: Color,
+    },
+    /// Score reached the next level, which loads after a short pause
+    LevelComplete,
+    /// Effect of the power-up eaten as food of a given kind ran out
+    PowerUpExpired { kind: FoodKind },
+    /// Physics step is over, with the run score, snake length and run time reached so far
+    StepFinished {
+        score: u32,
+        length: f32,
+        run_time: f32,
+        is_ghost: bool,
+    },
+}
+
+/// Trait for subsystems that react to the game events.
+/// Events a listener is not interested in are simply ignored.
+///
+pub trait GameEventListener {
+    fn on_event(&mut self, event: &GameEvent);
+}
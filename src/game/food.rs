@@ -1,25 +1,356 @@
-use crate::game::consts;
 use crate::game::coords::Coords;
-use ggez::graphics::Rect;
+use crate::game::savestate::RectDef;
+use crate::game::{consts, maths, sim, snake::Snake};
+use ggez::graphics::{Color, Rect};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
 
-/// Structure for holding snake food information
+/// Enumeration of food types, each one has a different effect when eaten
 ///
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FoodKind {
+    Normal,
+    SpeedBoost,
+    Shrink,
+    ScoreDouble,
+    Ghost,
+    Shield,
+    Confusion,
+    Freeze,
+    Magnet,
+}
+
+impl FoodKind {
+    const ALL: [FoodKind; 9] = [
+        FoodKind::Normal,
+        FoodKind::SpeedBoost,
+        FoodKind::Shrink,
+        FoodKind::ScoreDouble,
+        FoodKind::Ghost,
+        FoodKind::Shield,
+        FoodKind::Confusion,
+        FoodKind::Freeze,
+        FoodKind::Magnet,
+    ];
+
+    /// Return how likely it is for this kind to be picked, relative to the others
+    ///
+    pub fn weight(&self) -> u32 {
+        match self {
+            FoodKind::Normal => 50,
+            FoodKind::SpeedBoost => 8,
+            FoodKind::Shrink => 8,
+            FoodKind::ScoreDouble => 7,
+            FoodKind::Ghost => 7,
+            FoodKind::Shield => 5,
+            FoodKind::Confusion => 5,
+            FoodKind::Freeze => 5,
+            FoodKind::Magnet => 5,
+        }
+    }
+
+    /// Return the color used to tint the food sprite
+    ///
+    pub fn color(&self) -> Color {
+        match self {
+            FoodKind::Normal => Color::from_rgb(255, 255, 255),
+            FoodKind::SpeedBoost => Color::from_rgb(255, 80, 80),
+            FoodKind::Shrink => Color::from_rgb(80, 160, 255),
+            FoodKind::ScoreDouble => Color::from_rgb(255, 215, 0),
+            FoodKind::Ghost => Color::from_rgb(200, 120, 255),
+            FoodKind::Shield => Color::from_rgb(80, 230, 200),
+            FoodKind::Confusion => Color::from_rgb(150, 40, 200),
+            FoodKind::Freeze => Color::from_rgb(170, 220, 255),
+            FoodKind::Magnet => Color::from_rgb(200, 200, 215),
+        }
+    }
+
+    /// Pick a random kind, according to the kind weights
+    ///
+    pub fn random_with(rng: &mut impl Rng) -> Self {
+        let weights = WeightedIndex::new(Self::ALL.iter().map(|kind| kind.weight())).unwrap();
+        Self::ALL[weights.sample(rng)]
+    }
+}
+
+/// Structure for holding snake food information.
+/// Food is moved by its middle point `pos`, `bbox` always follows it.
+///
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Food {
+    pub pos: Coords,
+    #[serde(with = "RectDef")]
     pub bbox: Rect,
+    pub kind: FoodKind,
+    pub age: f32,
+    pub max_age: f32,
+    pub pulse_phase: f32,
+    pub spawn_timer: f32,
 }
 
 impl Food {
-    /// Construct a `Food` instance with a random position, based on `consts::SCREEN_SIZE.x`.
+    /// Return the layer the food is drawn in, above the snake and obstacles
+    ///
+    pub fn z_order(&self) -> i32 {
+        consts::Z_ITEMS
+    }
+
+    /// Construct a `Food` instance with a random position inside `bounds`,
+    /// at least `border` away from the edges, and a random kind.
+    /// Both are drawn from the given random number generator.
+    ///
+    /// The position is snapped to the food-sized cells that the grid fallback
+    /// of `spawn_away_from` checks, so food always lies on one of them.
     ///
-    pub fn random() -> Self {
-        let pos = Coords::random(consts::FOOD_SIZE, consts::SCREEN_SIZE.x - consts::FOOD_SIZE);
+    pub fn random_with(rng: &mut impl Rng, bounds: Coords, border: f32, max_age: f32) -> Self {
+        let offset = consts::FOOD_SIZE + border + consts::FOOD_HALF_SIZE;
+        let origin = Coords::new(offset, offset);
+        let pos = Coords::random_with(rng, origin, bounds - origin);
+        let pos = (pos - origin).snap_to_grid(consts::FOOD_SIZE) + origin;
+        Self::at(pos, FoodKind::random_with(rng), max_age)
+    }
+
+    /// Construct a randomly placed `Food` instance, like `random_with` does,
+    /// that does not touch `snake` and is at least `min_distance` away from its head.
+    /// Places for which `blocked` returns true are skipped as well.
+    ///
+    /// After `consts::FOOD_SPAWN_RETRIES` misses every food-sized cell of the screen is checked
+    /// with `sim::find_free_cell`, preferring ones far enough from the head.
+    /// Returns `None` only when there is no free cell at all.
+    ///
+    pub fn spawn_away_from(
+        rng: &mut impl Rng,
+        bounds: Coords,
+        border: f32,
+        max_age: f32,
+        snake: &Snake,
+        min_distance: f32,
+        blocked: impl Fn(&Food) -> bool,
+    ) -> Option<Self> {
+        let head = snake.head_position();
+        let is_free = |food: &Food| !snake.collide(&food.bbox) && !blocked(food);
+        let is_far = |food: &Food| food.center().distance(&head) >= min_distance;
+
+        for _ in 0..consts::FOOD_SPAWN_RETRIES {
+            let food = Self::random_with(rng, bounds, border, max_age);
+            if is_free(&food) && is_far(&food) {
+                return Some(food);
+            }
+        }
+
+        let kind = FoodKind::random_with(rng);
+        let cell = Coords::new(consts::FOOD_SIZE, consts::FOOD_SIZE);
+        let pos = sim::find_free_cell(
+            rng,
+            bounds,
+            consts::FOOD_SIZE + border,
+            cell,
+            head,
+            min_distance,
+            |area| {
+                let center = Coords::new(area.x + area.w / 2., area.y + area.h / 2.);
+                is_free(&Self::at(center, kind, max_age))
+            },
+        )?;
+
+        Some(Self::at(pos, kind, max_age))
+    }
+
+    fn at(pos: Coords, kind: FoodKind, max_age: f32) -> Self {
         Self {
-            bbox: Rect::new(
-                pos.x - consts::FOOD_HALF_SIZE,
-                pos.y - consts::FOOD_HALF_SIZE,
-                consts::FOOD_SIZE,
-                consts::FOOD_SIZE,
-            ),
+            pos,
+            bbox: Self::bbox_around(pos),
+            kind,
+            age: 0.,
+            max_age,
+            pulse_phase: 0.,
+            spawn_timer: consts::FOOD_SPAWN_SECS,
+        }
+    }
+
+    fn bbox_around(pos: Coords) -> Rect {
+        Rect::new(
+            pos.x - consts::FOOD_HALF_SIZE,
+            pos.y - consts::FOOD_HALF_SIZE,
+            consts::FOOD_SIZE,
+            consts::FOOD_SIZE,
+        )
+    }
+
+    /// Return the middle point of the food
+    ///
+    pub fn center(&self) -> Coords {
+        self.pos
+    }
+
+    /// Move the food by `dist` towards `target`, stopping once it gets there
+    ///
+    pub fn move_towards(&mut self, target: Coords, dist: f32) {
+        let offset = target - self.pos;
+        self.pos = if offset.magnitude() <= dist {
+            target
+        } else {
+            self.pos + offset.normalize() * dist
+        };
+        self.bbox = Self::bbox_around(self.pos);
+    }
+
+    /// Move the food inside `bounds`, keeping the same margin from the edges
+    /// as the randomly placed food has.
+    ///
+    pub fn clamp_to(&mut self, bounds: Coords) {
+        let margin = Coords::new(consts::FOOD_SIZE, consts::FOOD_SIZE);
+        self.pos = (self.pos - margin).clamp_to_bounds(bounds - margin * 2.) + margin;
+        self.bbox = Self::bbox_around(self.pos);
+    }
+
+    /// Make the food older by `dt` and advance its pulse and spawn animations
+    ///
+    pub fn tick(&mut self, dt: f32) {
+        self.age += dt;
+        self.tick_frozen(dt);
+    }
+
+    /// Advance the pulse and spawn animations by `dt`, without making the food older
+    ///
+    pub fn tick_frozen(&mut self, dt: f32) {
+        self.spawn_timer = f32::max(self.spawn_timer - dt, 0.);
+        self.pulse_phase = (self.pulse_phase + dt * consts::PULSE_FREQ) % std::f32::consts::TAU;
+    }
+
+    /// Check if the spawn animation has ended. Only then the food can be eaten.
+    ///
+    pub fn is_materialized(&self) -> bool {
+        self.spawn_timer <= 0.
+    }
+
+    /// Return the size of the food sprite, growing from nothing to `consts::FOOD_SIZE`
+    /// during the spawn animation
+    ///
+    pub fn spawn_size(&self) -> Coords {
+        let full = Coords::new(consts::FOOD_SIZE, consts::FOOD_SIZE);
+        let progress = 1. - self.spawn_timer / consts::FOOD_SPAWN_SECS;
+        Coords::default().lerp(full, maths::apply_easing(progress, maths::ease_out_cubic))
+    }
+
+    /// Return the size multiplier of the food sprite, oscillating around 1.
+    /// Only the drawn sprite is scaled, `bbox` used for collisions stays the same.
+    ///
+    pub fn pulse_scale(&self) -> f32 {
+        1. + consts::PULSE_SCALE * self.pulse_phase.sin()
+    }
+
+    /// Return part of the lifetime that is still left, between 0 and 1
+    ///
+    pub fn lifetime_left(&self) -> f32 {
+        f32::clamp(1. - self.age / self.max_age, 0., 1.)
+    }
+
+    /// Return opacity of the food sprite, fading out towards the end of the lifetime
+    ///
+    pub fn opacity(&self) -> f32 {
+        maths::lerp(1., 0., maths::ease_in_cubic(1. - self.lifetime_left()))
+    }
+
+    /// Check if food lived long enough to disappear
+    ///
+    pub fn is_expired(&self) -> bool {
+        self.age >= self.max_age
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn spawned_food_avoids_snake_obstacles_and_head() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let bounds = consts::SCREEN_SIZE;
+        let mut snake = Snake::new(bounds.x / 2., bounds.y / 2.);
+        snake.grow(consts::SNAKE_WIDTH * 10.);
+        let obstacle = Rect::new(100., 100., 200., 60.);
+        let head = snake.head_position();
+
+        for _ in 0..500 {
+            let food = Food::spawn_away_from(
+                &mut rng,
+                bounds,
+                0.,
+                consts::FOOD_MAX_AGE,
+                &snake,
+                consts::FOOD_MIN_HEAD_DISTANCE,
+                |food| food.bbox.overlaps(&obstacle),
+            )
+            .expect("board has plenty of room");
+            assert!(!snake.collide(&food.bbox));
+            assert!(!food.bbox.overlaps(&obstacle));
+            assert!(food.center().distance(&head) >= consts::FOOD_MIN_HEAD_DISTANCE);
+            let origin = consts::FOOD_SIZE + consts::FOOD_HALF_SIZE;
+            let cell = food.center() - Coords::new(origin, origin);
+            assert_eq!(cell.snap_to_grid(consts::FOOD_SIZE), cell);
         }
     }
+
+    #[test]
+    fn grid_fallback_finds_the_last_free_cell() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let bounds = consts::SCREEN_SIZE;
+        let snake = Snake::new(bounds.x / 2., bounds.y / 2.);
+        let free = Coords::new(
+            consts::FOOD_SIZE + consts::FOOD_HALF_SIZE,
+            consts::FOOD_SIZE + consts::FOOD_HALF_SIZE,
+        );
+        let food = Food::spawn_away_from(
+            &mut rng,
+            bounds,
+            0.,
+            consts::FOOD_MAX_AGE,
+            &snake,
+            consts::FOOD_MIN_HEAD_DISTANCE,
+            |food| food.center() != free,
+        )
+        .expect("one cell is left free");
+        assert_eq!(food.center(), free);
+
+        let none = Food::spawn_away_from(
+            &mut rng,
+            bounds,
+            0.,
+            consts::FOOD_MAX_AGE,
+            &snake,
+            0.,
+            |_| true,
+        );
+        assert!(none.is_none());
+    }
+
+    #[test]
+    fn frozen_food_materializes_but_does_not_age() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let snake = Snake::new(0., 0.);
+        let mut food = Food::spawn_away_from(
+            &mut rng,
+            consts::SCREEN_SIZE,
+            0.,
+            consts::FOOD_SPAWN_SECS,
+            &snake,
+            0.,
+            |_| false,
+        )
+        .unwrap();
+        assert!(!food.is_materialized());
+
+        food.tick_frozen(consts::FOOD_SPAWN_SECS * 2.);
+        assert!(food.is_materialized());
+        assert!(!food.is_expired());
+        assert_eq!(food.lifetime_left(), 1.);
+
+        food.tick(consts::FOOD_SPAWN_SECS);
+        assert!(food.is_expired());
+    }
 }
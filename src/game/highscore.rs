@@ -0,0 +1,83 @@
+//! Module with persistent high score storage
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::game::consts;
+
+/// Layout of the high score file
+///
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct HighScoreFile {
+    best: u32,
+}
+
+/// Structure for reading and writing the all-time best score.
+///
+/// Score is kept as a JSON file in the user's data directory.
+/// When the directory cannot be determined, the score lives only in memory.
+///
+pub struct HighScoreManager {
+    path: Option<PathBuf>,
+    best: u32,
+}
+
+impl HighScoreManager {
+    /// Create new `HighScoreManager` and load the currently stored best score.
+    ///
+    pub fn new() -> Self {
+        let path =
+            dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::HIGHSCORE_FILE));
+        let mut manager = Self { path, best: 0 };
+        manager.best = manager.load();
+        manager
+    }
+
+    /// Read the best score from the file. Missing or malformed file counts as 0.
+    ///
+    pub fn load(&self) -> u32 {
+        self.path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<HighScoreFile>(&content).ok())
+            .map_or(0, |file| file.best)
+    }
+
+    /// Store `score` as the new best score, if it beats the current one.
+    ///
+    pub fn save(&mut self, score: u32) {
+        if score <= self.best {
+            return;
+        }
+        self.best = score;
+
+        if let Some(path) = &self.path {
+            let content = serde_json::to_string(&HighScoreFile { best: score })
+                .expect("Error while serializing high score");
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(path, content));
+            if let Err(err) = written {
+                eprintln!("Could not save high score to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Check if `score` is better than the stored best score.
+    ///
+    pub fn is_new_record(&self, score: u32) -> bool {
+        score > self.best
+    }
+
+    /// Return the current best score
+    ///
+    pub fn best(&self) -> u32 {
+        self.best
+    }
+}
+
+impl Default for HighScoreManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,265 @@
+//! Module with Strings struct, holding all in-game texts in one language
+use serde::de::{self, Deserializer, IntoDeserializer};
+use serde::Deserialize;
+use std::{collections::HashMap, fmt};
+
+use crate::game::{achievements::Achievement, death::DeathCause};
+
+/// Structure with every text shown in the game, read from a TOML file per language.
+///
+/// Each `{}` in a text is replaced with a value by `fill`, in the order they appear.
+/// Texts missing from the file stay in English.
+///
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Strings {
+    pub score_label: String,
+    pub highscore_label: String,
+    pub length_label: String,
+    pub combo_label: String,
+    pub level_label: String,
+    pub level_clear: String,
+    pub remaining_time_label: String,
+    pub speed_label: String,
+    pub difficulty_label: String,
+    pub pregame_difficulty: String,
+    pub wrap_walls: String,
+    pub record: String,
+    pub two_player: String,
+    pub player_two_score_label: String,
+    pub final_score: String,
+    pub two_player_final_score: String,
+    pub survival_time: String,
+    pub stats: String,
+    pub on: String,
+    pub off: String,
+    pub autoplay: String,
+    pub press_to_start: String,
+    pub paused: String,
+    pub game_over: String,
+    pub new_record: String,
+    pub achievement_unlocked: String,
+    pub daily_label: String,
+    pub speedrun_completed: String,
+    pub shield_blocked: String,
+    pub screenshot_saved: String,
+    pub screenshot_failed: String,
+    pub menu_title: String,
+    pub menu_play: String,
+    pub menu_settings: String,
+    pub menu_quit: String,
+    pub settings_title: String,
+    pub settings_hint: String,
+    pub settings_export_hint: String,
+    pub palette_exported: String,
+    pub palette_export_failed: String,
+    pub master_volume: String,
+    pub music_volume: String,
+    pub sfx_volume: String,
+    pub theme: String,
+    pub controls: String,
+    pub difficulty: String,
+    pub death_wall: String,
+    pub death_self_collision: String,
+    pub death_obstacle: String,
+    pub death_void: String,
+    pub death_other_snake: String,
+    pub death_hunger: String,
+    pub death_time_up: String,
+    #[serde(deserialize_with = "with_english_names")]
+    pub achievements: HashMap<Achievement, String>,
+}
+
+impl Strings {
+    /// Return the text for a toggle state
+    ///
+    pub fn on_off(&self, on: bool) -> &str {
+        if on {
+            &self.on
+        } else {
+            &self.off
+        }
+    }
+
+    /// Return the explanation of the death shown on the game-over screen
+    ///
+    pub fn death_cause(&self, cause: &DeathCause) -> String {
+        match cause {
+            DeathCause::Wall => self.death_wall.clone(),
+            DeathCause::SelfCollision => self.death_self_collision.clone(),
+            DeathCause::Obstacle(name) => fill(&self.death_obstacle, &[name]),
+            DeathCause::Void => self.death_void.clone(),
+            DeathCause::OtherSnake => self.death_other_snake.clone(),
+            DeathCause::Hunger => self.death_hunger.clone(),
+            DeathCause::TimeUp => self.death_time_up.clone(),
+        }
+    }
+
+    /// Return the name of the achievement shown when it gets unlocked
+    ///
+    pub fn achievement_name(&self, achievement: Achievement) -> &str {
+        self.achievements
+            .get(&achievement)
+            .map_or("?", String::as_str)
+    }
+}
+
+/// English achievement names, the same as in `en.toml`
+///
+fn english_achievement_names() -> HashMap<Achievement, String> {
+    [
+        (Achievement::FirstFood, "First bite"),
+        (Achievement::Score100, "Score 100"),
+        (Achievement::Score500, "Score 500"),
+        (Achievement::Score1000, "Score 1000"),
+        (Achievement::Length25, "Growing up"),
+        (Achievement::Length50, "Long boi"),
+        (Achievement::Length100, "Anaconda"),
+        (Achievement::SurviveMinute, "Survivor"),
+        (Achievement::SurviveFiveMinutes, "Marathon"),
+        (Achievement::EatWhileGhost, "Ghostly snack"),
+        (Achievement::EatSpeedBoost, "Need for speed"),
+        (Achievement::EatShrink, "Diet"),
+        (Achievement::EatScoreDouble, "Double trouble"),
+        (Achievement::BigBite, "Big bite"),
+        (Achievement::OutlastPowerUp, "Worn off"),
+        (Achievement::ClearLevel, "Level up"),
+        (Achievement::DieByHunger, "Starving artist"),
+        (Achievement::DieByWall, "Wall hugger"),
+        (Achievement::BiteYourself, "Ouroboros"),
+        (Achievement::FallIntoVoid, "Into the void"),
+        (Achievement::Play10Games, "Regular"),
+        (Achievement::Play100Games, "Addict"),
+        (Achievement::TotalScore10000, "Hoarder"),
+    ]
+    .iter()
+    .map(|&(achievement, name)| (achievement, name.to_owned()))
+    .collect()
+}
+
+/// Read achievement names from the file, keyed by the variant names,
+/// keeping the English ones of those that are missing
+///
+fn with_english_names<'de, D>(deserializer: D) -> Result<HashMap<Achievement, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut names = english_achievement_names();
+    for (key, name) in HashMap::<String, String>::deserialize(deserializer)? {
+        let achievement = Achievement::deserialize(key.as_str().into_deserializer())
+            .map_err(|err: de::value::Error| de::Error::custom(err))?;
+        names.insert(achievement, name);
+    }
+    Ok(names)
+}
+
+impl Default for Strings {
+    /// English texts, the same as in `en.toml`
+    ///
+    fn default() -> Self {
+        Self {
+            score_label: "Score: {}".to_owned(),
+            highscore_label: "Best: {}".to_owned(),
+            length_label: "Length: {}".to_owned(),
+            combo_label: "Combo x{}".to_owned(),
+            level_label: "Level: {}".to_owned(),
+            level_clear: "Level {} cleared!".to_owned(),
+            remaining_time_label: "Time: {}".to_owned(),
+            speed_label: "Speed: {}%".to_owned(),
+            difficulty_label: "Difficulty: {}".to_owned(),
+            pregame_difficulty: "Difficulty: {} ({} to change)".to_owned(),
+            wrap_walls: "Wrap walls: {} ({} to toggle)".to_owned(),
+            record: "Record replay: {} ({} to toggle, {} to play last)".to_owned(),
+            two_player: "Two players: {} ({} to toggle)".to_owned(),
+            player_two_score_label: "P2 score: {}".to_owned(),
+            final_score: "Score: {} — press any key".to_owned(),
+            two_player_final_score: "P1: {}  P2: {} — press any key".to_owned(),
+            survival_time: "Survived: {} s — press any key".to_owned(),
+            stats: "Games: {}  Average: {}  Best: {}  Longest: {}".to_owned(),
+            on: "ON".to_owned(),
+            off: "OFF".to_owned(),
+            autoplay: "Auto-play: {} ({} to toggle)".to_owned(),
+            press_to_start: "Press {} to start the game".to_owned(),
+            paused: "PAUSED".to_owned(),
+            game_over: "GAME OVER".to_owned(),
+            new_record: "NEW RECORD!".to_owned(),
+            achievement_unlocked: "Achievement unlocked: {}".to_owned(),
+            daily_label: "Daily challenge {}".to_owned(),
+            speedrun_completed: "COMPLETED in {}".to_owned(),
+            shield_blocked: "SHIELD BLOCKED!".to_owned(),
+            screenshot_saved: "Screenshot saved!".to_owned(),
+            screenshot_failed: "Could not save screenshot".to_owned(),
+            menu_title: "SNAKE".to_owned(),
+            menu_play: "Play".to_owned(),
+            menu_settings: "Settings".to_owned(),
+            menu_quit: "Quit".to_owned(),
+            settings_title: "SETTINGS".to_owned(),
+            settings_hint: "{}/{} to select, {}/{} to change, {} to save".to_owned(),
+            settings_export_hint: "{} to export the colors".to_owned(),
+            palette_exported: "Colors saved to {}".to_owned(),
+            palette_export_failed: "Could not save the colors".to_owned(),
+            master_volume: "Master volume".to_owned(),
+            music_volume: "Music volume".to_owned(),
+            sfx_volume: "SFX volume".to_owned(),
+            theme: "Theme".to_owned(),
+            controls: "Controls".to_owned(),
+            difficulty: "Difficulty".to_owned(),
+            death_wall: "You hit the wall!".to_owned(),
+            death_self_collision: "You bit yourself!".to_owned(),
+            death_obstacle: "You crashed into the {}!".to_owned(),
+            death_void: "You fell into the void!".to_owned(),
+            death_other_snake: "You crashed into the other snake!".to_owned(),
+            death_hunger: "You starved!".to_owned(),
+            death_time_up: "Time is up!".to_owned(),
+            achievements: english_achievement_names(),
+        }
+    }
+}
+
+/// Replace each `{}` in `template` with the next of `args`.
+/// Placeholders without a matching argument are left as they are.
+///
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut pieces = template.split("{}");
+    let mut filled = pieces.next().unwrap_or_default().to_owned();
+    let mut args = args.iter();
+    for piece in pieces {
+        match args.next() {
+            Some(arg) => filled.push_str(&arg.to_string()),
+            None => filled.push_str("{}"),
+        }
+        filled.push_str(piece);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::consts;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn bundled_languages_name_every_achievement() {
+        for lang in &["en", "pl"] {
+            let path = Path::new(consts::STRINGS_DIR).join(format!("{}.toml", lang));
+            let content = fs::read_to_string(&path).unwrap();
+            let strings: Strings = toml::from_str(&content).unwrap();
+            assert_eq!(
+                strings.achievements.len(),
+                english_achievement_names().len()
+            );
+        }
+    }
+
+    #[test]
+    fn missing_achievement_names_stay_english() {
+        let strings: Strings =
+            toml::from_str("[achievements]\nFirstFood = \"Pierwszy kęs\"").unwrap();
+        assert_eq!(
+            strings.achievement_name(Achievement::FirstFood),
+            "Pierwszy kęs"
+        );
+        assert_eq!(strings.achievement_name(Achievement::BigBite), "Big bite");
+    }
+}
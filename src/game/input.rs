@@ -0,0 +1,294 @@
+//! Module that translates keyboard and gamepad events into snake directions
+use ggez::event::{Axis, Button, KeyCode};
+
+use crate::game::config::{self, ControlScheme, GameConfig};
+use crate::game::{consts, coords::Coords, direction::Direction, menu::MenuAction};
+
+/// Return the name of the first key in `keys`, as used in the config file,
+/// or `?` when no key is bound
+///
+pub fn key_name(keys: &[KeyCode]) -> &'static str {
+    keys.first()
+        .and_then(|&keycode| config::keycode_name(keycode))
+        .unwrap_or("?")
+}
+
+/// Structure that turns keyboard keys, gamepad d-pad and left analog stick
+/// into a single stream of `Direction` values.
+///
+/// The analog stick reports only the changes of its direction,
+/// so holding it does not flood the input queue.
+///
+#[derive(Default, Debug)]
+pub struct InputSource {
+    stick: Coords,
+    stick_dir: Option<Direction>,
+}
+
+impl InputSource {
+    /// Return direction bound to the gamepad d-pad button
+    ///
+    pub fn button_direction(&self, btn: Button) -> Option<Direction> {
+        match btn {
+            Button::DPadUp => Some(Direction::Up),
+            Button::DPadDown => Some(Direction::Down),
+            Button::DPadLeft => Some(Direction::Left),
+            Button::DPadRight => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Update the left stick position and return the new direction,
+    /// when the stick was pushed past `consts::STICK_DEAD_ZONE` towards another side.
+    ///
+    pub fn axis_direction(&mut self, axis: Axis, value: f32) -> Option<Direction> {
+        match axis {
+            Axis::LeftStickX => self.stick.x = value,
+            // stick Y axis points up, screen Y axis points down
+            Axis::LeftStickY => self.stick.y = -value,
+            _ => return None,
+        }
+        let stick = self.stick;
+
+        let dir = if stick.magnitude() < consts::STICK_DEAD_ZONE {
+            None
+        } else if stick.x.abs() > stick.y.abs() {
+            Some(if stick.x > 0. {
+                Direction::Right
+            } else {
+                Direction::Left
+            })
+        } else {
+            Some(if stick.y > 0. {
+                Direction::Down
+            } else {
+                Direction::Up
+            })
+        };
+
+        let changed = dir != self.stick_dir;
+        self.stick_dir = dir;
+        dir.filter(|_| changed)
+    }
+}
+
+/// Enumeration of everything the player can ask the game to do
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum GameAction {
+    Move(Direction),
+    MovePlayerTwo(Direction),
+    Start,
+    Pause,
+    Reset,
+    Restart,
+    CycleDifficulty,
+    ToggleWrapWalls,
+    ToggleRecording,
+    PlayReplay,
+    ToggleAutoPlay,
+    ToggleTwoPlayer,
+}
+
+/// Structure that binds keyboard keys to game actions.
+/// Each action can be bound to any number of keys.
+///
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub start: Vec<KeyCode>,
+    pub pause: Vec<KeyCode>,
+    pub reset: Vec<KeyCode>,
+    pub restart: Vec<KeyCode>,
+    pub difficulty: Vec<KeyCode>,
+    pub wrap_walls: Vec<KeyCode>,
+    pub record: Vec<KeyCode>,
+    pub replay: Vec<KeyCode>,
+    pub autoplay: Vec<KeyCode>,
+    pub two_player: Vec<KeyCode>,
+    pub player_two_up: Vec<KeyCode>,
+    pub player_two_down: Vec<KeyCode>,
+    pub player_two_left: Vec<KeyCode>,
+    pub player_two_right: Vec<KeyCode>,
+    pub fullscreen: Vec<KeyCode>,
+    pub select: Vec<KeyCode>,
+    pub mute: Vec<KeyCode>,
+    pub grid: Vec<KeyCode>,
+    pub screenshot: Vec<KeyCode>,
+    pub palette: Vec<KeyCode>,
+    pub export_palette: Vec<KeyCode>,
+}
+
+impl Default for KeyBindings {
+    /// Bindings of the default config, arrow keys and WASD for movement
+    ///
+    fn default() -> Self {
+        Self::from_config(&GameConfig::default())
+    }
+}
+
+impl KeyBindings {
+    /// Create bindings from the key names in the config.
+    /// Names are checked by `GameConfig::validate`, unknown ones are skipped.
+    ///
+    /// Movement keys come from the config only with `ControlScheme::Custom`,
+    /// other schemes bind just the arrows or just WASD.
+    ///
+    pub fn from_config(cfg: &GameConfig) -> Self {
+        let keys = |names: &[String]| {
+            names
+                .iter()
+                .filter_map(|name| config::parse_keycode(name))
+                .collect()
+        };
+        let scheme_keys = |scheme| {
+            (
+                config::direction_keys(scheme, Direction::Up),
+                config::direction_keys(scheme, Direction::Down),
+                config::direction_keys(scheme, Direction::Left),
+                config::direction_keys(scheme, Direction::Right),
+            )
+        };
+        let (up, down, left, right) = match cfg.control_scheme {
+            ControlScheme::Arrows => scheme_keys(Direction::from_keycode_arrows),
+            ControlScheme::Wasd => scheme_keys(Direction::from_keycode_wasd),
+            ControlScheme::Custom => (
+                keys(&cfg.keys.up),
+                keys(&cfg.keys.down),
+                keys(&cfg.keys.left),
+                keys(&cfg.keys.right),
+            ),
+        };
+        Self {
+            up,
+            down,
+            left,
+            right,
+            start: keys(&cfg.keys.start),
+            pause: keys(&cfg.keys.pause),
+            reset: keys(&cfg.keys.reset),
+            restart: keys(&cfg.keys.restart),
+            difficulty: keys(&cfg.keys.difficulty),
+            wrap_walls: keys(&cfg.keys.wrap_walls),
+            record: keys(&cfg.keys.record),
+            replay: keys(&cfg.keys.replay),
+            autoplay: keys(&cfg.keys.autoplay),
+            two_player: keys(&cfg.keys.two_player),
+            player_two_up: keys(&cfg.keys.player_two_up),
+            player_two_down: keys(&cfg.keys.player_two_down),
+            player_two_left: keys(&cfg.keys.player_two_left),
+            player_two_right: keys(&cfg.keys.player_two_right),
+            fullscreen: keys(&cfg.keys.fullscreen),
+            select: keys(&cfg.keys.select),
+            mute: keys(&cfg.keys.mute),
+            grid: keys(&cfg.keys.grid),
+            screenshot: keys(&cfg.keys.screenshot),
+            palette: keys(&cfg.keys.palette),
+            export_palette: keys(&cfg.keys.export_palette),
+        }
+    }
+
+    /// Return the in-game action bound to the key
+    ///
+    pub fn map(&self, kc: KeyCode) -> Option<GameAction> {
+        [
+            (&self.up, GameAction::Move(Direction::Up)),
+            (&self.down, GameAction::Move(Direction::Down)),
+            (&self.left, GameAction::Move(Direction::Left)),
+            (&self.right, GameAction::Move(Direction::Right)),
+            (&self.start, GameAction::Start),
+            (&self.pause, GameAction::Pause),
+            (&self.reset, GameAction::Reset),
+            (&self.restart, GameAction::Restart),
+        ]
+        .iter()
+        .find(|(keys, _)| keys.contains(&kc))
+        .map(|&(_, action)| action)
+    }
+
+    /// Return the second player movement bound to the key.
+    /// In the two-player mode these keys take precedence over the in-game ones.
+    ///
+    pub fn map_player_two(&self, kc: KeyCode) -> Option<GameAction> {
+        [
+            (&self.player_two_up, Direction::Up),
+            (&self.player_two_down, Direction::Down),
+            (&self.player_two_left, Direction::Left),
+            (&self.player_two_right, Direction::Right),
+        ]
+        .iter()
+        .find(|(keys, _)| keys.contains(&kc))
+        .map(|&(_, dir)| GameAction::MovePlayerTwo(dir))
+    }
+
+    /// Check if the key switches between fullscreen and windowed mode.
+    /// It works in every game state, so it is not one of the `GameAction`s.
+    ///
+    pub fn is_fullscreen_toggle(&self, kc: KeyCode) -> bool {
+        self.fullscreen.contains(&kc)
+    }
+
+    /// Check if the key mutes or unmutes all audio.
+    /// Just like the fullscreen toggle, it works in every game state.
+    ///
+    pub fn is_mute_toggle(&self, kc: KeyCode) -> bool {
+        self.mute.contains(&kc)
+    }
+
+    /// Check if the key shows or hides the grid overlay, in every game state
+    ///
+    pub fn is_grid_toggle(&self, kc: KeyCode) -> bool {
+        self.grid.contains(&kc)
+    }
+
+    /// Check if the key saves a screenshot, in every game state
+    ///
+    pub fn is_screenshot_key(&self, kc: KeyCode) -> bool {
+        self.screenshot.contains(&kc)
+    }
+
+    /// Check if the key switches to the next color palette, in every game state
+    ///
+    pub fn is_palette_key(&self, kc: KeyCode) -> bool {
+        self.palette.contains(&kc)
+    }
+
+    /// Return the menu action bound to the key.
+    /// Movement keys move the selection or change the value, pause key goes back.
+    /// The palette export key works only on the settings screen.
+    ///
+    pub fn map_menu(&self, kc: KeyCode) -> Option<MenuAction> {
+        [
+            (&self.up, MenuAction::Up),
+            (&self.down, MenuAction::Down),
+            (&self.left, MenuAction::Left),
+            (&self.right, MenuAction::Right),
+            (&self.select, MenuAction::Select),
+            (&self.pause, MenuAction::Back),
+            (&self.export_palette, MenuAction::ExportPalette),
+        ]
+        .iter()
+        .find(|(keys, _)| keys.contains(&kc))
+        .map(|&(_, action)| action)
+    }
+
+    /// Return the action bound to the key on the pre-game screen.
+    /// These keys take precedence over the in-game ones there.
+    ///
+    pub fn map_pregame(&self, kc: KeyCode) -> Option<GameAction> {
+        [
+            (&self.difficulty, GameAction::CycleDifficulty),
+            (&self.wrap_walls, GameAction::ToggleWrapWalls),
+            (&self.record, GameAction::ToggleRecording),
+            (&self.replay, GameAction::PlayReplay),
+            (&self.autoplay, GameAction::ToggleAutoPlay),
+            (&self.two_player, GameAction::ToggleTwoPlayer),
+        ]
+        .iter()
+        .find(|(keys, _)| keys.contains(&kc))
+        .map(|&(_, action)| action)
+    }
+}
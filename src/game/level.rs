@@ -0,0 +1,183 @@
+//! Module with Level struct, loaded from the JSON files in `consts::LEVELS_DIR`
+use ggez::{graphics::Rect, GameError, GameResult};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+use crate::game::{board::TileKind, consts, coords::Coords, direction::Direction};
+use crate::game::{obstacle::Obstacle, portal::Portal};
+
+/// Layout of a single rectangular area in the level file
+///
+#[derive(Deserialize, Debug)]
+struct RectFile {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+/// Layout of a single obstacle in the level file, its name is optional
+///
+#[derive(Deserialize, Debug)]
+struct ObstacleFile {
+    #[serde(flatten)]
+    area: RectFile,
+    name: Option<String>,
+}
+
+/// Layout of a single exit position in the level file
+///
+#[derive(Deserialize, Debug)]
+struct PosFile {
+    x: f32,
+    y: f32,
+}
+
+/// Layout of a single portal in the level file
+///
+#[derive(Deserialize, Debug)]
+struct PortalFile {
+    entry: RectFile,
+    exit_pos: PosFile,
+    exit_dir: Direction,
+}
+
+/// Layout of a single terrain area in the level file
+///
+#[derive(Deserialize, Debug)]
+struct TerrainFile {
+    kind: TileKind,
+    area: RectFile,
+}
+
+/// Layout of the level file
+///
+#[derive(Deserialize, Debug)]
+struct LevelFile {
+    id: u32,
+    speed_mult: f32,
+    food_count: usize,
+    #[serde(default)]
+    obstacles: Vec<ObstacleFile>,
+    #[serde(default)]
+    portals: Vec<PortalFile>,
+    #[serde(default)]
+    terrain: Vec<TerrainFile>,
+}
+
+impl RectFile {
+    fn to_rect(&self) -> Rect {
+        Rect::new(self.x, self.y, self.w, self.h)
+    }
+}
+
+/// Structure describing a single level: its obstacles, portals, terrain,
+/// snake speed and amount of food.
+///
+/// Terrain areas are applied in order, so later ones cover the earlier ones.
+///
+#[derive(PartialEq, Clone, Debug)]
+pub struct Level {
+    pub id: u32,
+    pub speed_mult: f32,
+    pub obstacles: Vec<Obstacle>,
+    pub portals: Vec<Portal>,
+    pub terrain: Vec<(TileKind, Rect)>,
+    pub food_count: usize,
+}
+
+impl Level {
+    /// Create the level used when no level files could be loaded
+    ///
+    pub fn fallback(food_count: usize) -> Self {
+        Self {
+            id: 1,
+            speed_mult: 1.,
+            obstacles: consts::DEFAULT_OBSTACLES
+                .iter()
+                .map(|&bbox| Obstacle::new(bbox, consts::OBSTACLE_NAME))
+                .collect(),
+            portals: Vec::new(),
+            terrain: Vec::new(),
+            food_count,
+        }
+    }
+
+    /// Read a level from the given JSON file and validate it
+    ///
+    pub fn from_json(path: &Path) -> GameResult<Self> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            GameError::FilesystemError(format!("Could not read {}: {}", path.display(), err))
+        })?;
+        let level: LevelFile = serde_json::from_str(&content).map_err(|err| {
+            GameError::FilesystemError(format!("Malformed level {}: {}", path.display(), err))
+        })?;
+        if level.speed_mult <= 0. {
+            return Err(GameError::FilesystemError(format!(
+                "Level {} has to have a positive speed multiplier",
+                path.display()
+            )));
+        }
+        if level.food_count == 0 {
+            return Err(GameError::FilesystemError(format!(
+                "Level {} has to have at least one food item",
+                path.display()
+            )));
+        }
+
+        Ok(Self {
+            id: level.id,
+            speed_mult: level.speed_mult,
+            obstacles: level
+                .obstacles
+                .iter()
+                .map(|obstacle| {
+                    let name = obstacle.name.as_deref().unwrap_or(consts::OBSTACLE_NAME);
+                    Obstacle::new(obstacle.area.to_rect(), name)
+                })
+                .collect(),
+            portals: level
+                .portals
+                .iter()
+                .map(|portal| {
+                    Portal::new(
+                        portal.entry.to_rect(),
+                        Coords::new(portal.exit_pos.x, portal.exit_pos.y),
+                        portal.exit_dir,
+                    )
+                })
+                .collect(),
+            terrain: level
+                .terrain
+                .iter()
+                .map(|terrain| (terrain.kind, terrain.area.to_rect()))
+                .collect(),
+            food_count: level.food_count,
+        })
+    }
+
+    /// Read every `.json` level from the directory, ordered by their ids.
+    /// Files that cannot be read are reported and skipped.
+    ///
+    pub fn load_all(dir: &Path) -> Vec<Self> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Could not read levels from {}: {}", dir.display(), err);
+                return Vec::new();
+            }
+        };
+
+        let mut levels: Vec<Self> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                Self::from_json(&path)
+                    .map_err(|err| eprintln!("{}", err))
+                    .ok()
+            })
+            .collect();
+        levels.sort_by_key(|level| level.id);
+        levels
+    }
+}
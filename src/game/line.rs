@@ -1,19 +1,24 @@
 use ggez::{
-    graphics::{self, Color, Mesh, Rect},
+    graphics::{self, Color, Mesh, MeshBuilder, Rect},
     Context,
 };
 
-use crate::game::{consts, coords::Coords, direction::Direction};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{consts, coords::Coords, direction::Direction, savestate::SegmentSnapshot};
 
 use super::segment::{Growable, Renderable};
 
-/// Straight segment of a snake
+/// Straight segment of a snake.
+/// Ends from before the last move are kept for the interpolated drawing.
 ///
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Line {
     pub beg: Coords,
     pub end: Coords,
     pub dir: Direction,
+    pub prev_beg: Coords,
+    pub prev_end: Coords,
 }
 
 impl Line {
@@ -25,43 +30,65 @@ impl Line {
             beg: pos,
             end: pos + dir.as_coords() * 0.01,
             dir,
+            prev_beg: pos,
+            prev_end: pos,
         }
     }
 
-    /// Return current length
+    /// Return the point halfway between both ends
     ///
-    pub fn size(&self) -> f32 {
-        match self.dir {
-            Direction::Up | Direction::Down => (self.end.y - self.beg.y).abs(),
-            Direction::Left | Direction::Right => (self.end.x - self.beg.x).abs(),
+    pub fn midpoint(&self) -> Coords {
+        (self.beg + self.end) / 2.
+    }
+
+    /// Check if `p` lies inside the bounding box,
+    /// widened by `consts::POINT_EPSILON` to accept points right on its edge
+    ///
+    pub fn contains_point(&self, p: Coords) -> bool {
+        let bbox = self.bounding_box();
+        p.x >= bbox.left() - consts::POINT_EPSILON
+            && p.x <= bbox.right() + consts::POINT_EPSILON
+            && p.y >= bbox.top() - consts::POINT_EPSILON
+            && p.y <= bbox.bottom() + consts::POINT_EPSILON
+    }
+
+    /// Return the `Line` placed `alpha` of the way from its previous position to the current one
+    ///
+    fn shown(&self, alpha: f32) -> Self {
+        Line {
+            beg: self.prev_beg.lerp(self.beg, alpha),
+            end: self.prev_end.lerp(self.end, alpha),
+            ..*self
         }
     }
 }
 
 impl Growable for Line {
     fn grow(&mut self, dist: f32) -> f32 {
-        match self.dir {
-            Direction::Up => self.end.y -= dist,
-            Direction::Down => self.end.y += dist,
-            Direction::Left => self.end.x -= dist,
-            Direction::Right => self.end.x += dist,
-        };
+        self.end += self.dir.as_coords() * dist;
 
         0.
     }
 
     fn shrink(&mut self, dist: f32) -> f32 {
         let left = f32::clamp(dist - self.size(), 0., dist);
-        match self.dir {
-            Direction::Up => self.beg.y -= dist,
-            Direction::Down => self.beg.y += dist,
-            Direction::Left => self.beg.x -= dist,
-            Direction::Right => self.beg.x += dist,
-        };
+        self.beg += self.dir.as_coords() * dist;
 
         left
     }
 
+    fn size(&self) -> f32 {
+        if self.dir.is_vertical() {
+            (self.end.y - self.beg.y).abs()
+        } else {
+            (self.end.x - self.beg.x).abs()
+        }
+    }
+
+    fn beg(&self) -> Coords {
+        self.beg
+    }
+
     fn end(&self) -> Coords {
         self.end
     }
@@ -69,9 +96,31 @@ impl Growable for Line {
     fn direction(&self) -> Direction {
         self.dir
     }
+
+    fn shift(&mut self, offset: Coords) {
+        self.beg += offset;
+        self.end += offset;
+        self.prev_beg += offset;
+        self.prev_end += offset;
+    }
+
+    fn store_previous(&mut self) {
+        self.prev_beg = self.beg;
+        self.prev_end = self.end;
+    }
+
+    fn snapshot(&self) -> SegmentSnapshot {
+        SegmentSnapshot::Line(*self)
+    }
 }
 
 impl Renderable for Line {
+    /// Exact area of the straight part, computed from the length instead of the bounding box
+    ///
+    fn area(&self) -> f32 {
+        consts::SNAKE_WIDTH * self.size()
+    }
+
     fn bounding_box(&self) -> Rect {
         let (x, y, w, h) = match self.dir {
             Direction::Up => (
@@ -103,28 +152,54 @@ impl Renderable for Line {
         Rect::new(x, y, w, h)
     }
 
-    fn draw(&self, ctx: &mut Context) {
+    fn tip(&self, alpha: f32) -> Coords {
+        self.prev_end.lerp(self.end, alpha)
+    }
+
+    fn joint(&self, alpha: f32) -> Option<Coords> {
+        Some(self.tip(alpha))
+    }
+
+    fn add_to_mesh(&self, builder: &mut MeshBuilder, alpha: f32, color: Color) {
+        builder.rectangle(
+            graphics::DrawMode::fill(),
+            self.shown(alpha).bounding_box(),
+            color,
+        );
+    }
+
+    fn draw_debug(&self, ctx: &mut Context, alpha: f32) {
         let mesh = Mesh::new_rectangle(
             ctx,
-            graphics::DrawMode::fill(),
-            self.bounding_box(),
-            Color::from_rgb(255, 255, 0),
+            graphics::DrawMode::stroke(1.),
+            self.shown(alpha).bounding_box(),
+            Color::from_rgb(255, 0, 0),
         )
         .unwrap();
         graphics::draw(ctx, &mesh, graphics::DrawParam::default())
-            .expect("Error while drawing Line");
-
-        #[cfg(feature = "debug")]
-        {
-            let mesh = Mesh::new_rectangle(
-                ctx,
-                graphics::DrawMode::stroke(1.),
-                self.get_bbox(),
-                Color::from_rgb(255, 0, 0),
-            )
-            .unwrap();
-            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
-                .expect("Error while drawing Line border");
+            .expect("Error while drawing Line border");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounding_box_covers_the_line_in_every_direction() {
+        for &dir in &Direction::all() {
+            let mut line = Line::new(Coords::new(100., 100.), dir);
+            line.grow(40.);
+            let bbox = line.bounding_box();
+            assert!(line.contains_point(line.beg), "{:?} misses the start", dir);
+            assert!(line.contains_point(line.end), "{:?} misses the end", dir);
+            assert!(
+                line.contains_point(line.midpoint()),
+                "{:?} misses the middle",
+                dir
+            );
+            let across = if dir.is_vertical() { bbox.w } else { bbox.h };
+            assert_eq!(across, consts::SNAKE_WIDTH);
         }
     }
 }
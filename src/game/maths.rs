@@ -0,0 +1,202 @@
+//! Module with small math helpers used for animations and interpolation
+
+/// Linear interpolation between `a` and `b`, where `t` of 0 gives `a` and 1 gives `b`
+///
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Interpolation between hues in degrees, going the shorter way around the color wheel.
+/// The result is between 0 and 360.
+///
+pub fn lerp_hue(a: f32, b: f32, t: f32) -> f32 {
+    let diff = (b - a + 180.).rem_euclid(360.) - 180.;
+    (a + diff * t).rem_euclid(360.)
+}
+
+/// Quadratic easing that starts slowly and speeds up, `t` between 0 and 1
+///
+pub fn ease_in_quad(t: f32) -> f32 {
+    t * t
+}
+
+/// Quadratic easing that starts fast and slows down, `t` between 0 and 1
+///
+pub fn ease_out_quad(t: f32) -> f32 {
+    let inv = 1. - t;
+    1. - inv * inv
+}
+
+/// Quadratic easing that is slow on both ends and fast in the middle, `t` between 0 and 1
+///
+pub fn ease_in_out_quad(t: f32) -> f32 {
+    if t < 0.5 {
+        2. * t * t
+    } else {
+        let inv = -2. * t + 2.;
+        1. - inv * inv / 2.
+    }
+}
+
+/// Cubic easing that starts slowly and speeds up, `t` between 0 and 1
+///
+pub fn ease_in_cubic(t: f32) -> f32 {
+    t * t * t
+}
+
+/// Cubic easing that starts fast and slows down, `t` between 0 and 1
+///
+pub fn ease_out_cubic(t: f32) -> f32 {
+    let inv = 1. - t;
+    1. - inv * inv * inv
+}
+
+/// Cubic easing that is slow on both ends and fast in the middle, `t` between 0 and 1
+///
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+    if t < 0.5 {
+        4. * t * t * t
+    } else {
+        let inv = -2. * t + 2.;
+        1. - inv * inv * inv / 2.
+    }
+}
+
+/// Apply easing `f` to `t`, clamped to the range between 0 and 1 first
+///
+pub fn apply_easing(t: f32, f: fn(f32) -> f32) -> f32 {
+    f(f32::clamp(t, 0., 1.))
+}
+
+/// Convert a color from HSL to RGB.
+/// Hue is in degrees and wraps around 360, saturation, lightness and the result are between 0 and 1.
+///
+pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.) / 60.;
+    let chroma = (1. - (2. * l - 1.).abs()) * s;
+    let x = chroma * (1. - (h % 2. - 1.).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let m = l - chroma / 2.;
+
+    (r + m, g + m, b + m)
+}
+
+/// Convert a color from RGB to HSL, the inverse of `hsl_to_rgb`.
+/// Gray colors get the hue of 0.
+///
+pub fn rgb_to_hsl(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.;
+    let chroma = max - min;
+    if chroma <= 0. {
+        return (0., 0., l);
+    }
+
+    let s = chroma / (1. - (2. * l - 1.).abs());
+    let h = if max == r {
+        ((g - b) / chroma).rem_euclid(6.)
+    } else if max == g {
+        (b - r) / chroma + 2.
+    } else {
+        (r - g) / chroma + 4.
+    };
+
+    (h * 60., s, l)
+}
+
+/// Number of decimal digits needed to write `n`, 1 for zero
+///
+pub fn digit_count(n: u32) -> u32 {
+    n.checked_ilog10().map_or(1, |log| log + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Easing = fn(f32) -> f32;
+
+    const EASINGS: [(&str, Easing); 6] = [
+        ("ease_in_quad", ease_in_quad),
+        ("ease_out_quad", ease_out_quad),
+        ("ease_in_out_quad", ease_in_out_quad),
+        ("ease_in_cubic", ease_in_cubic),
+        ("ease_out_cubic", ease_out_cubic),
+        ("ease_in_out_cubic", ease_in_out_cubic),
+    ];
+
+    #[test]
+    fn easings_keep_the_ends() {
+        for (name, f) in EASINGS {
+            assert_eq!(f(0.), 0., "{}(0)", name);
+            assert!((f(1.) - 1.).abs() < f32::EPSILON, "{}(1)", name);
+        }
+    }
+
+    #[test]
+    fn easings_are_monotonic() {
+        for (name, f) in EASINGS {
+            let mut prev = f(0.);
+            for step in 1..=100 {
+                let value = f(step as f32 / 100.);
+                assert!(value >= prev, "{} decreases at step {}", name, step);
+                assert!((0. ..=1.).contains(&value), "{} leaves [0, 1]", name);
+                prev = value;
+            }
+        }
+    }
+
+    #[test]
+    fn hsl_round_trip_keeps_the_color() {
+        for &(r, g, b) in &[
+            (0., 0., 0.),
+            (1., 1., 1.),
+            (0.5, 0.5, 0.5),
+            (1., 0., 0.),
+            (0.2, 0.8, 0.4),
+            (0.1, 0.3, 0.9),
+            (0.9, 0.2, 0.7),
+        ] {
+            let (h, s, l) = rgb_to_hsl(r, g, b);
+            let (r2, g2, b2) = hsl_to_rgb(h, s, l);
+            for (before, after) in [(r, r2), (g, g2), (b, b2)] {
+                assert!((before - after).abs() < 1e-5, "{:?}", (r, g, b));
+            }
+        }
+    }
+
+    #[test]
+    fn lerp_hue_goes_the_shorter_way() {
+        assert_eq!(lerp_hue(10., 50., 0.5), 30.);
+        assert_eq!(lerp_hue(350., 30., 0.5), 10.);
+        assert_eq!(lerp_hue(30., 350., 0.75), 0.);
+        assert_eq!(lerp_hue(350., 30., 0.), 350.);
+    }
+
+    #[test]
+    fn digit_count_at_powers_of_ten() {
+        assert_eq!(digit_count(0), 1);
+        for k in 1..=9 {
+            let power = 10u32.pow(k);
+            assert_eq!(digit_count(power - 1), k, "digit_count({})", power - 1);
+            assert_eq!(digit_count(power), k + 1, "digit_count({})", power);
+        }
+        assert_eq!(digit_count(u32::MAX), 10);
+    }
+
+    #[test]
+    fn apply_easing_clamps_t() {
+        for (name, f) in EASINGS {
+            assert_eq!(apply_easing(-1., f), f(0.), "{}", name);
+            assert_eq!(apply_easing(2., f), f(1.), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Module with MenuData struct, managing the main menu shown at start-up
+use ggez::{
+    graphics::{self, Color, Font, Text, TextFragment},
+    Context,
+};
+
+use crate::game::{i18n::Strings, renderer::Renderer, theme::ColorTheme};
+
+pub const HIGHLIGHT_COLOR: Color = Color::new(1., 0.86, 0.24, 1.);
+
+/// Enumeration of the main menu items, in the order they are shown
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MenuItem {
+    Play,
+    Settings,
+    Quit,
+}
+
+impl MenuItem {
+    /// Returns all items, in the order they are shown
+    ///
+    pub fn all() -> [MenuItem; 3] {
+        [MenuItem::Play, MenuItem::Settings, MenuItem::Quit]
+    }
+
+    /// Returns the text shown for the item
+    ///
+    pub fn label<'a>(&self, strings: &'a Strings) -> &'a str {
+        match self {
+            MenuItem::Play => &strings.menu_play,
+            MenuItem::Settings => &strings.menu_settings,
+            MenuItem::Quit => &strings.menu_quit,
+        }
+    }
+}
+
+/// Enumeration of everything the player can do in a menu
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+    ExportPalette,
+}
+
+/// Structure for holding the main menu texts and the selected item
+///
+pub struct MenuData {
+    pub selected: usize,
+    pub title_txt: Text,
+    pub item_txts: Vec<Text>,
+}
+
+impl MenuData {
+    /// Creates new `MenuData` with the first item selected.
+    ///
+    pub fn new(strings: &Strings, font: Font) -> Self {
+        Self {
+            selected: 0,
+            title_txt: Text::new(
+                TextFragment::new(strings.menu_title.as_str())
+                    .scale(graphics::Scale::uniform(96.))
+                    .font(font),
+            ),
+            item_txts: MenuItem::all()
+                .iter()
+                .map(|item| {
+                    Text::new(
+                        TextFragment::new(item.label(strings))
+                            .scale(graphics::Scale::uniform(48.))
+                            .font(font),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the currently selected item
+    ///
+    pub fn selected_item(&self) -> MenuItem {
+        MenuItem::all()[self.selected]
+    }
+
+    /// Moves the selection to the previous item, wrapping around
+    ///
+    pub fn select_prev(&mut self) {
+        let len = self.item_txts.len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    /// Moves the selection to the next item, wrapping around
+    ///
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.item_txts.len();
+    }
+
+    /// Draws the title and all items centered on the screen,
+    /// with the selected item highlighted.
+    ///
+    pub fn draw(&self, ctx: &mut Context, theme: &ColorTheme) {
+        Renderer::draw_centered_text_with_outline(ctx, &self.title_txt, -160., theme);
+        for (idx, txt) in self.item_txts.iter().enumerate() {
+            let color = if idx == self.selected {
+                HIGHLIGHT_COLOR
+            } else {
+                theme.text_color
+            };
+            Renderer::draw_centered_colored_text_with_outline(ctx, txt, idx as f32 * 70., color);
+        }
+    }
+}
@@ -1,85 +1,287 @@
 #[macro_use]
 mod consts;
+mod achievements;
+mod ai;
+mod board;
+mod config;
 mod coords;
+mod daily;
+mod danger;
+mod death;
+mod debug;
+mod difficulty;
 mod direction;
+mod event;
 mod food;
+mod highscore;
+mod i18n;
+mod input;
+mod level;
 mod line;
+mod maths;
+mod menu;
+mod obstacle;
+mod palette;
+mod particles;
+mod popup;
+mod portal;
+mod powerup;
 mod renderer;
+mod replay;
 mod resourceloader;
+mod savestate;
 mod segment;
+mod settings;
+mod sim;
 mod snake;
+mod spatial;
+mod speedrun;
 mod state;
+mod stats;
+mod theme;
 mod turn;
 
 use ggez::{
-    event::{EventHandler, KeyCode, KeyMods},
+    event::{Axis, Button, EventHandler, GamepadId, KeyCode, KeyMods},
     graphics, Context, GameResult,
 };
 use state::GameState;
 use std::time::Instant;
 
+pub use crate::game::config::{GameConfig, StartupArgs};
 pub use crate::game::consts::*;
-pub use crate::game::state::GameData;
+pub use crate::game::sim::{run_benchmark, run_headless};
+pub use crate::game::state::{GameData, GameMode};
 
-use self::{coords::Coords, direction::Direction, renderer::Renderer};
+use self::{coords::Coords, input::GameAction, menu::MenuAction, renderer::Renderer};
 
 impl EventHandler for GameData {
     fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
         let prev_time = std::mem::replace(&mut self.delta_time, Instant::now());
         let time_delta = self.delta_time.duration_since(prev_time).as_secs_f32();
+        if let Some(frame_stats) = &mut self.frame_stats {
+            frame_stats.push(time_delta);
+        }
+        self.update_music(time_delta);
+        self.update_notice(time_delta);
+        self.update_hue(time_delta);
 
         match self.state {
-            GameState::PreGame => {}
-            GameState::Game => {
-                self.update_input(time_delta);
-                self.update_snake(time_delta);
-            }
+            GameState::Menu | GameState::Settings | GameState::PreGame => {}
+            GameState::Game => self.step(time_delta),
+            GameState::Respawning => self.update_respawn(time_delta),
+            GameState::GameOver => self.update_effects(time_delta),
+            GameState::Paused => {}
         }
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        Renderer::draw_bg(ctx, &self.resources.bg_image);
-        Renderer::draw_snake(ctx, &self.snake);
-        Renderer::draw_food(ctx, &self.food, &self.resources.food_image);
+        let shake = self.shake_offset();
+        graphics::push_transform(
+            ctx,
+            Some(graphics::DrawParam::new().dest(shake).to_matrix()),
+        );
+        graphics::apply_transformations(ctx)?;
+
+        self.draw_static_layer(ctx)?;
+        if self.show_grid {
+            Renderer::draw_grid(ctx, self.screen);
+        }
+        self.draw_world(ctx);
 
         match self.state {
+            GameState::Menu => self.menu.draw(ctx, &self.theme),
+            GameState::Settings => self.settings.draw(
+                ctx,
+                &self.config,
+                &self.strings,
+                &self.theme,
+                self.resources.font,
+            ),
             GameState::PreGame => {
-                let (xdim, ydim) = self.pregame_txt.dimensions(ctx);
-                let (xdim, ydim) = (xdim as f32, ydim as f32);
-                Renderer::draw_text_with_outline(
+                Renderer::draw_centered_text_with_outline(ctx, &self.pregame_txt, 0., &self.theme);
+                Renderer::draw_centered_text_with_outline(
                     ctx,
-                    &self.pregame_txt,
-                    Coords::new(
-                        consts::SCREEN_SIZE.x / 2. - xdim / 2.,
-                        consts::SCREEN_SIZE.y / 2. - ydim / 2.,
-                    ),
+                    &self.pregame_difficulty_txt,
+                    60.,
+                    &self.theme,
                 );
+                Renderer::draw_centered_text_with_outline(
+                    ctx,
+                    &self.wrap_walls_txt,
+                    100.,
+                    &self.theme,
+                );
+                Renderer::draw_centered_text_with_outline(ctx, &self.record_txt, 140., &self.theme);
+                Renderer::draw_centered_text_with_outline(
+                    ctx,
+                    &self.two_player_txt,
+                    180.,
+                    &self.theme,
+                );
+                if self.mode == GameMode::AutoPlay {
+                    Renderer::draw_centered_text_with_outline(
+                        ctx,
+                        &self.autoplay_txt,
+                        220.,
+                        &self.theme,
+                    );
+                }
             }
             GameState::Game => {
-                Renderer::draw_text_with_outline(ctx, &self.score_txt, Coords::new(10., 10.));
+                self.draw_hud(ctx);
+                self.draw_level_clear(ctx);
+            }
+            GameState::Respawning => {
+                // flashes with `consts::RESPAWN_FLASH_FREQ`, fading out until the snake is back
+                let pulse = 0.5 + 0.5 * (self.respawn_timer * consts::RESPAWN_FLASH_FREQ).cos();
+                let fade = maths::apply_easing(
+                    self.respawn_timer / consts::RESPAWN_SECS,
+                    maths::ease_in_out_cubic,
+                );
+                let mut color = self.theme.head_color;
+                color.a = 0.3 * pulse * fade;
+                Renderer::draw_flash(ctx, self.screen, color);
+                self.draw_hud(ctx);
+            }
+            GameState::Paused => {
+                self.draw_hud(ctx);
+                self.draw_level_clear(ctx);
+                Renderer::draw_centered_text_with_outline(ctx, &self.paused_txt, 0., &self.theme);
+            }
+            GameState::GameOver => {
+                Renderer::draw_centered_text_with_outline(
+                    ctx,
+                    &self.game_over_txt,
+                    -80.,
+                    &self.theme,
+                );
+                Renderer::draw_centered_text_with_outline(ctx, &self.death_txt, -10., &self.theme);
+                Renderer::draw_centered_text_with_outline(
+                    ctx,
+                    &self.final_score_txt,
+                    40.,
+                    &self.theme,
+                );
+                Renderer::draw_centered_text_with_outline(ctx, &self.stats_txt, 100., &self.theme);
             }
         }
 
+        if let Some(notice) = &self.notice {
+            notice.draw(ctx, self.resources.font);
+        }
+
+        if let Some(frame_stats) = &self.frame_stats {
+            let x = self.screen.x - consts::DEBUG_GRAPH_SIZE.x - 10.;
+            frame_stats.draw_graph(ctx, x, 50., &self.theme);
+            debug::draw_counters(
+                ctx,
+                Coords::new(x, 120.),
+                self.total_moves,
+                self.total_turns,
+                &self.theme,
+            );
+        }
+
+        graphics::pop_transform(ctx);
+        graphics::apply_transformations(ctx)?;
         graphics::present(ctx)
     }
 
-    fn key_down_event(&mut self, _ctx: &mut Context, keycode: KeyCode, _km: KeyMods, _rpt: bool) {
-        if let Some(dir) = match keycode {
-            KeyCode::W => Some(Direction::Up),
-            KeyCode::S => Some(Direction::Down),
-            KeyCode::A => Some(Direction::Left),
-            KeyCode::D => Some(Direction::Right),
-            _ => None,
-        } {
-            if self.inputs.is_empty() || self.inputs.back().unwrap() != &dir {
-                self.inputs.push_front(dir);
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, _km: KeyMods, _rpt: bool) {
+        if self.key_bindings.is_fullscreen_toggle(keycode) {
+            self.toggle_fullscreen(ctx);
+            return;
+        }
+        if self.key_bindings.is_mute_toggle(keycode) {
+            self.toggle_mute();
+            return;
+        }
+        if self.key_bindings.is_grid_toggle(keycode) {
+            self.show_grid = !self.show_grid;
+            return;
+        }
+        if self.key_bindings.is_palette_key(keycode) {
+            self.cycle_palette();
+            return;
+        }
+        if self.key_bindings.is_screenshot_key(keycode) {
+            self.take_screenshot(ctx);
+            return;
+        }
+
+        if self.state == GameState::GameOver {
+            self.confirm_game_over();
+            return;
+        }
+
+        if self.state == GameState::Menu || self.state == GameState::Settings {
+            if let Some(action) = self.key_bindings.map_menu(keycode) {
+                self.handle_menu_action(ctx, action);
+            }
+            return;
+        }
+
+        let pregame_action = if self.state == GameState::PreGame {
+            self.key_bindings.map_pregame(keycode)
+        } else {
+            None
+        };
+
+        let player_two_action = if self.two_player {
+            self.key_bindings.map_player_two(keycode)
+        } else {
+            None
+        };
+
+        if let Some(action) = pregame_action
+            .or(player_two_action)
+            .or_else(|| self.key_bindings.map(keycode))
+        {
+            self.handle_action(action);
+        }
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        graphics::set_screen_coordinates(ctx, graphics::Rect::new(0., 0., width, height))
+            .expect("Error while setting screen coordinates");
+        self.on_resize(width, height);
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
+        if self.state == GameState::Menu || self.state == GameState::Settings {
+            let action = match btn {
+                Button::DPadUp => Some(MenuAction::Up),
+                Button::DPadDown => Some(MenuAction::Down),
+                Button::DPadLeft => Some(MenuAction::Left),
+                Button::DPadRight => Some(MenuAction::Right),
+                Button::Start | Button::South => Some(MenuAction::Select),
+                Button::East | Button::Select => Some(MenuAction::Back),
+                _ => None,
+            };
+            if let Some(action) = action {
+                self.handle_menu_action(ctx, action);
             }
-        } else if keycode == KeyCode::Space {
+            return;
+        }
+
+        if let Some(dir) = self.input_source.button_direction(btn) {
+            self.handle_action(GameAction::Move(dir));
+        } else if btn == Button::Start {
             if self.state == GameState::PreGame {
-                self.state = GameState::Game;
+                self.handle_action(GameAction::Start);
+            } else {
+                self.handle_action(GameAction::Pause);
             }
-            self.inputs.clear();
+        } else if btn == Button::Select {
+            self.handle_action(GameAction::Reset);
+        }
+    }
+
+    fn gamepad_axis_event(&mut self, _ctx: &mut Context, axis: Axis, value: f32, _id: GamepadId) {
+        if let Some(dir) = self.input_source.axis_direction(axis, value) {
+            self.handle_action(GameAction::Move(dir));
         }
     }
 }
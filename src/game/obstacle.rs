@@ -0,0 +1,43 @@
+//! Module with Obstacle struct
+use ggez::graphics::{self, Color, MeshBuilder, Rect};
+
+use crate::game::coords::Coords;
+
+use super::segment::Renderable;
+
+/// Static wall placed on the board, hitting it ends the game.
+/// `name` tells the player what they crashed into.
+///
+#[derive(PartialEq, Clone, Debug)]
+pub struct Obstacle {
+    pub bbox: Rect,
+    pub name: String,
+}
+
+impl Obstacle {
+    /// Create new `Obstacle` covering the given area
+    ///
+    pub fn new(bbox: Rect, name: &str) -> Self {
+        Self {
+            bbox,
+            name: name.to_string(),
+        }
+    }
+}
+
+impl Renderable for Obstacle {
+    fn bounding_box(&self) -> Rect {
+        self.bbox
+    }
+
+    fn tip(&self, _alpha: f32) -> Coords {
+        Coords::new(
+            self.bbox.x + self.bbox.w / 2.,
+            self.bbox.y + self.bbox.h / 2.,
+        )
+    }
+
+    fn add_to_mesh(&self, builder: &mut MeshBuilder, _alpha: f32, color: Color) {
+        builder.rectangle(graphics::DrawMode::fill(), self.bbox, color);
+    }
+}
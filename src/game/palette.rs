@@ -0,0 +1,110 @@
+//! Module with ColorPalette struct, color schemes that can be shared as JSON files
+use ggez::{graphics::Color, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::game::theme::ColorTheme;
+
+/// Structure with the colors of a custom scheme, as written in the palette file.
+///
+/// Snake fades from the first to the last of `snake_colors`,
+/// `ui_color` is used for all plain texts.
+/// Colors the palette does not name are taken from the theme it is applied to.
+///
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ColorPalette {
+    pub name: String,
+    pub background: [u8; 3],
+    pub snake_colors: Vec<[u8; 3]>,
+    pub food_color: [u8; 3],
+    pub ui_color: [u8; 3],
+}
+
+impl ColorPalette {
+    /// Read a palette from the given JSON file and validate it
+    ///
+    pub fn from_json(path: &Path) -> GameResult<Self> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            GameError::FilesystemError(format!("Could not read {}: {}", path.display(), err))
+        })?;
+        let palette: Self = serde_json::from_str(&content).map_err(|err| {
+            GameError::FilesystemError(format!("Malformed palette {}: {}", path.display(), err))
+        })?;
+        if palette.snake_colors.is_empty() {
+            return Err(GameError::FilesystemError(format!(
+                "Palette {} has to have at least one snake color",
+                path.display()
+            )));
+        }
+
+        Ok(palette)
+    }
+
+    /// Return a palette with the colors of `theme`,
+    /// with the snake fading from its head color to its tail color
+    ///
+    pub fn from_theme(name: &str, theme: &ColorTheme) -> Self {
+        let rgb = |color: Color| {
+            let (r, g, b) = color.to_rgb();
+            [r, g, b]
+        };
+        Self {
+            name: name.to_owned(),
+            background: rgb(theme.background_color),
+            snake_colors: vec![rgb(theme.head_color), rgb(theme.tail_color)],
+            food_color: rgb(theme.food_color),
+            ui_color: rgb(theme.text_color),
+        }
+    }
+
+    /// Write the palette into the given JSON file
+    ///
+    pub fn save(&self, path: &Path) -> GameResult {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| GameError::FilesystemError(err.to_string()))?;
+        fs::write(path, content).map_err(|err| {
+            GameError::FilesystemError(format!("Could not write {}: {}", path.display(), err))
+        })
+    }
+
+    /// Read every `.json` palette from the directory, ordered by their file names.
+    /// Files that cannot be read are reported and skipped.
+    ///
+    pub fn load_all(dir: &Path) -> Vec<Self> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Could not read palettes from {}: {}", dir.display(), err);
+                return Vec::new();
+            }
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .filter_map(|path| {
+                Self::from_json(path)
+                    .map_err(|err| eprintln!("{}", err))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Return `base` with the colors of this palette put in
+    ///
+    pub fn to_theme(&self, base: &ColorTheme) -> ColorTheme {
+        let color = |[r, g, b]: [u8; 3]| Color::from_rgb(r, g, b);
+        ColorTheme {
+            head_color: color(self.snake_colors[0]),
+            tail_color: color(*self.snake_colors.last().unwrap()),
+            background_color: color(self.background),
+            food_color: color(self.food_color),
+            text_color: color(self.ui_color),
+            ..*base
+        }
+    }
+}
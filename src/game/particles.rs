@@ -0,0 +1,152 @@
+//! Module with a simple particle system used for eating and death effects
+use ggez::{
+    graphics::{self, Color, MeshBuilder},
+    Context,
+};
+use rand::Rng;
+
+use crate::game::event::{GameEvent, GameEventListener};
+use crate::game::{consts, coords::Coords, direction::Direction, maths};
+use crate::game::{snake::Snake, theme::ColorTheme};
+
+/// Single particle, that moves with a constant velocity and fades out
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Particle {
+    pub pos: Coords,
+    pub vel: Coords,
+    pub life: f32,
+    pub color: Color,
+}
+
+/// Structure for holding all living particles
+///
+#[derive(Default)]
+pub struct ParticleSystem {
+    pub particles: Vec<Particle>,
+}
+
+impl ParticleSystem {
+    /// Spawn `count` particles on `pos`, flying outwards in random directions.
+    ///
+    /// Particles use their own random number generator,
+    /// so they never change the food placement of a seeded run.
+    ///
+    pub fn spawn_burst(&mut self, pos: Coords, count: usize, color: Color) {
+        let mut rng = rand::thread_rng();
+        self.particles.extend((0..count).map(|_| {
+            let angle = rng.gen_range(0. ..std::f32::consts::TAU);
+            let speed = rng.gen_range(0.3..1.) * consts::PARTICLE_SPEED;
+            let (sin, cos) = angle.sin_cos();
+            Particle {
+                pos,
+                vel: Coords::new(cos, sin) * speed,
+                life: rng.gen_range(0.5..1.) * consts::PARTICLE_LIFE_SECS,
+                color,
+            }
+        }));
+    }
+
+    /// Spawn `count` particles on `pos`, flying within `consts::PARTICLE_SPRAY_ANGLE`
+    /// of the given direction.
+    ///
+    pub fn spawn_spray(&mut self, pos: Coords, count: usize, color: Color, dir: Direction) {
+        let mut rng = rand::thread_rng();
+        let dir = dir.as_coords();
+        let base = dir.y.atan2(dir.x);
+        let spread = consts::PARTICLE_SPRAY_ANGLE;
+        self.particles.extend((0..count).map(|_| {
+            let angle = base + rng.gen_range(-spread..spread);
+            let speed = rng.gen_range(0.3..1.) * consts::PARTICLE_SPEED;
+            let (sin, cos) = angle.sin_cos();
+            Particle {
+                pos,
+                vel: Coords::new(cos, sin) * speed,
+                life: rng.gen_range(0.5..1.) * consts::PARTICLE_LIFE_SECS,
+                color,
+            }
+        }));
+    }
+
+    /// Spawn a burst on the middle of every segment of `snake`, in the colors the segments
+    /// are drawn with, so the whole body breaks apart. Each segment gets
+    /// `consts::DEATH_PARTICLES_PER_CELL` for every snake-wide square of its area.
+    ///
+    pub fn spawn_along(&mut self, snake: &Snake, theme: &ColorTheme) {
+        let centers = snake.segment_centers();
+        let total = centers.len();
+        let cell_area = consts::SNAKE_WIDTH * consts::SNAKE_WIDTH;
+        for (idx, (center, segment)) in centers.into_iter().zip(&snake.body).enumerate() {
+            let count = (segment.area() / cell_area * consts::DEATH_PARTICLES_PER_CELL).ceil();
+            self.spawn_burst(center, count as usize, theme.segment_color(idx, total));
+        }
+    }
+
+    /// Move all particles and remove the ones that ran out of life
+    ///
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.pos += particle.vel * dt;
+            particle.life -= dt;
+        }
+        self.particles.retain(|particle| particle.life > 0.);
+    }
+
+    /// Remove all particles
+    ///
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Return the layer the particles are drawn in
+    ///
+    pub fn z_order(&self) -> i32 {
+        consts::Z_PARTICLES
+    }
+
+    /// Draws all particles as small circles, fading out with the life left
+    ///
+    pub fn draw(&self, ctx: &mut Context) {
+        if self.particles.is_empty() {
+            return;
+        }
+
+        let mut builder = MeshBuilder::new();
+        for particle in &self.particles {
+            let mut color = particle.color;
+            color.a *= maths::apply_easing(
+                particle.life / consts::PARTICLE_LIFE_SECS,
+                maths::ease_out_quad,
+            );
+            builder.circle(
+                graphics::DrawMode::fill(),
+                particle.pos,
+                consts::PARTICLE_RADIUS,
+                0.5,
+                color,
+            );
+        }
+        if let Ok(mesh) = builder.build(ctx) {
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+                .expect("Error while drawing particles");
+        }
+    }
+}
+
+impl GameEventListener for ParticleSystem {
+    /// Bursts where food was eaten, in its color, and where a snake died, in the snake color
+    ///
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::FoodEaten { pos, kind, .. } => {
+                self.spawn_burst(pos, consts::EAT_PARTICLES, kind.color())
+            }
+            GameEvent::SnakeDied { pos, color, .. } => {
+                self.spawn_burst(pos, consts::DEATH_PARTICLES, color)
+            }
+            GameEvent::LevelComplete
+            | GameEvent::PowerUpExpired { .. }
+            | GameEvent::StepFinished { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Module with ScorePopup struct
+use ggez::{
+    graphics::{self, Font, Text, TextFragment},
+    Context,
+};
+
+use crate::game::{consts, coords::Coords, maths};
+
+/// Points earned for eating food, shown for a moment where the food was.
+/// Short notices, like the one about a saved screenshot, are shown the same way.
+///
+#[derive(PartialEq, Clone, Debug)]
+pub struct ScorePopup {
+    pub pos: Coords,
+    pub text: String,
+    pub lifetime: f32,
+    pub max_lifetime: f32,
+}
+
+impl ScorePopup {
+    /// Create new `ScorePopup` with points `value` centered on `pos`
+    ///
+    pub fn new(pos: Coords, value: u32) -> Self {
+        Self {
+            pos,
+            text: format!("+{}", value),
+            lifetime: consts::POPUP_SECS,
+            max_lifetime: consts::POPUP_SECS,
+        }
+    }
+
+    /// Create new `ScorePopup` with the given `text` centered on `pos`,
+    /// shown for `consts::NOTICE_SECS`
+    ///
+    pub fn notice(pos: Coords, text: &str) -> Self {
+        Self {
+            pos,
+            text: text.to_string(),
+            lifetime: consts::NOTICE_SECS,
+            max_lifetime: consts::NOTICE_SECS,
+        }
+    }
+
+    /// Check if popup was shown long enough to disappear
+    ///
+    pub fn is_expired(&self) -> bool {
+        self.lifetime <= 0.
+    }
+
+    /// Return the layer the popup is drawn in, above everything else in the game world
+    ///
+    pub fn z_order(&self) -> i32 {
+        consts::Z_POPUPS
+    }
+
+    /// Draws the text with the given font. As the popup gets older
+    /// it drifts upwards, shrinks and fades out.
+    ///
+    pub fn draw(&self, ctx: &mut Context, font: Font) {
+        let progress = 1. - f32::clamp(self.lifetime / self.max_lifetime, 0., 1.);
+        let txt = Text::new(
+            TextFragment::new(self.text.as_str())
+                .scale(graphics::Scale::uniform(maths::lerp(16., 8., progress)))
+                .font(font),
+        );
+        let (width, height) = txt.dimensions(ctx);
+        let pos = Coords::new(
+            self.pos.x - width as f32 / 2.,
+            self.pos.y - height as f32 / 2. - consts::POPUP_DRIFT * maths::ease_out_quad(progress),
+        );
+
+        let mut color = graphics::WHITE;
+        color.a = 1. - progress;
+        graphics::draw(
+            ctx,
+            &txt,
+            graphics::DrawParam::default().dest(pos).color(color),
+        )
+        .expect("Error while drawing score popup");
+    }
+}
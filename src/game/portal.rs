@@ -0,0 +1,84 @@
+//! Module with Portal struct
+use ggez::{
+    graphics::{self, Color, Rect},
+    Context,
+};
+
+use crate::game::{consts, coords::Coords, direction::Direction, snake::Snake};
+
+/// Tile that warps the snake head, which entered it, to `exit_pos`,
+/// where it continues towards `exit_dir`.
+///
+/// Portals come in pairs, each one exits next to its partner.
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Portal {
+    pub entry: Rect,
+    pub exit_pos: Coords,
+    pub exit_dir: Direction,
+    pub swirl_phase: f32,
+}
+
+impl Portal {
+    /// Create new `Portal` with the entry covering the given area
+    ///
+    pub fn new(entry: Rect, exit_pos: Coords, exit_dir: Direction) -> Self {
+        Self {
+            entry,
+            exit_pos,
+            exit_dir,
+            swirl_phase: 0.,
+        }
+    }
+
+    /// Advance the swirl animation by `dt` seconds
+    ///
+    pub fn tick(&mut self, dt: f32) {
+        self.swirl_phase =
+            (self.swirl_phase + dt * consts::PORTAL_SWIRL_FREQ) % std::f32::consts::TAU;
+    }
+
+    /// Check if the head segment of `snake` covers the middle of the entry,
+    /// so that the snake warps only once it goes into the portal, not when it brushes against the edge.
+    ///
+    pub fn is_entered_by(&self, snake: &Snake) -> bool {
+        let middle = Coords::new(
+            self.entry.x + self.entry.w / 2.,
+            self.entry.y + self.entry.h / 2.,
+        );
+        snake.segment_at_position(middle) == Some(0)
+    }
+
+    /// Return the layer the portal is drawn in, the same as the one of food
+    ///
+    pub fn z_order(&self) -> i32 {
+        consts::Z_ITEMS
+    }
+
+    /// Draws the entry as nested rectangles, with colors cycling
+    /// from the outside to the middle, so they seem to swirl inwards.
+    ///
+    pub fn draw(&self, ctx: &mut Context) {
+        let mut builder = graphics::MeshBuilder::new();
+        for ring in 0..consts::PORTAL_RINGS {
+            let inset = ring as f32 / consts::PORTAL_RINGS as f32 / 2.;
+            let area = Rect::new(
+                self.entry.x + self.entry.w * inset,
+                self.entry.y + self.entry.h * inset,
+                self.entry.w * (1. - 2. * inset),
+                self.entry.h * (1. - 2. * inset),
+            );
+            let wave = self.swirl_phase - ring as f32;
+            let color = Color::new(
+                0.5 + 0.5 * wave.sin(),
+                0.2,
+                0.5 + 0.5 * (wave + std::f32::consts::FRAC_PI_2).sin(),
+                1.,
+            );
+            builder.rectangle(graphics::DrawMode::fill(), area, color);
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing Portal");
+    }
+}
@@ -0,0 +1,168 @@
+//! Module with PowerUpState struct
+use serde::{Deserialize, Serialize};
+
+use crate::game::{consts, food::FoodKind};
+
+/// Structure for tracking which power-up effects are active
+/// and how many seconds each of them has left.
+///
+/// After the shield blocks a crash, `shield_grace` keeps the snake safe for
+/// `consts::SHIELD_GRACE_SECS`, so it can steer away instead of crashing again at once.
+/// It is too short to be worth saving with the run.
+///
+#[derive(Default, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PowerUpState {
+    pub speed_boost: Option<f32>,
+    pub ghost: Option<f32>,
+    pub score_double: Option<f32>,
+    pub shield: Option<f32>,
+    pub confusion: Option<f32>,
+    pub freeze: Option<f32>,
+    pub magnet: Option<f32>,
+    #[serde(skip)]
+    pub shield_grace: Option<f32>,
+}
+
+impl PowerUpState {
+    /// Decrement all active timers by `dt`, clearing the ones that expired.
+    /// Returns the food kinds whose effects have just expired.
+    ///
+    pub fn tick(&mut self, dt: f32) -> Vec<FoodKind> {
+        let mut expired = Vec::new();
+        for (timer, kind) in [
+            (&mut self.speed_boost, FoodKind::SpeedBoost),
+            (&mut self.ghost, FoodKind::Ghost),
+            (&mut self.score_double, FoodKind::ScoreDouble),
+            (&mut self.shield, FoodKind::Shield),
+            (&mut self.confusion, FoodKind::Confusion),
+            (&mut self.freeze, FoodKind::Freeze),
+            (&mut self.magnet, FoodKind::Magnet),
+        ] {
+            let was_active = timer.is_some();
+            *timer = timer.map(|secs| secs - dt).filter(|&secs| secs > 0.);
+            if was_active && timer.is_none() {
+                expired.push(kind);
+            }
+        }
+        self.shield_grace = self
+            .shield_grace
+            .map(|secs| secs - dt)
+            .filter(|&secs| secs > 0.);
+        expired
+    }
+
+    /// Start the effect of a given food kind with its initial duration.
+    /// Kinds without a lasting effect are ignored.
+    ///
+    pub fn apply(&mut self, kind: FoodKind) {
+        match kind {
+            FoodKind::SpeedBoost => self.speed_boost = Some(consts::SPEED_BOOST_SECS),
+            FoodKind::Ghost => self.ghost = Some(consts::GHOST_SECS),
+            FoodKind::ScoreDouble => self.score_double = Some(consts::SCORE_DOUBLE_SECS),
+            FoodKind::Shield => self.shield = Some(consts::SHIELD_SECS),
+            FoodKind::Confusion => self.confusion = Some(consts::CONFUSION_SECS),
+            FoodKind::Freeze => self.freeze = Some(consts::FREEZE_SECS),
+            FoodKind::Magnet => self.magnet = Some(consts::MAGNET_SECS),
+            FoodKind::Normal | FoodKind::Shrink => {}
+        }
+    }
+
+    /// Check if the snake moves faster
+    ///
+    pub fn is_speed_boosted(&self) -> bool {
+        self.speed_boost.is_some()
+    }
+
+    /// Check if the snake ignores collisions
+    ///
+    pub fn is_ghost(&self) -> bool {
+        self.ghost.is_some()
+    }
+
+    /// Check if the points are doubled
+    ///
+    pub fn is_score_doubled(&self) -> bool {
+        self.score_double.is_some()
+    }
+
+    /// Check if the next crash is blocked
+    ///
+    pub fn is_shielded(&self) -> bool {
+        self.shield.is_some()
+    }
+
+    /// Check if the player controls are reversed
+    ///
+    pub fn is_confused(&self) -> bool {
+        self.confusion.is_some()
+    }
+
+    /// Check if the time stands still for everything but the snake
+    ///
+    pub fn is_frozen(&self) -> bool {
+        self.freeze.is_some()
+    }
+
+    /// Check if the nearest food is pulled towards the snake
+    ///
+    pub fn is_magnetic(&self) -> bool {
+        self.magnet.is_some()
+    }
+
+    /// Check if crashes are ignored for a moment, right after the shield blocked one
+    ///
+    pub fn is_in_shield_grace(&self) -> bool {
+        self.shield_grace.is_some()
+    }
+
+    /// Return `points` multiplied by the active score power-ups
+    ///
+    pub fn multiply_score(&self, points: u32) -> u32 {
+        if self.is_score_doubled() {
+            points * 2
+        } else {
+            points
+        }
+    }
+
+    /// Use up the shield, if it is active, starting the grace period after it.
+    /// Returns whether it was active.
+    ///
+    pub fn take_shield(&mut self) -> bool {
+        let taken = self.shield.take().is_some();
+        if taken {
+            self.shield_grace = Some(consts::SHIELD_GRACE_SECS);
+        }
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taking_the_shield_starts_a_short_grace() {
+        let mut power_ups = PowerUpState::default();
+        assert!(!power_ups.take_shield());
+        assert!(!power_ups.is_in_shield_grace());
+
+        power_ups.apply(FoodKind::Shield);
+        assert!(power_ups.take_shield());
+        assert!(!power_ups.is_shielded());
+        assert!(power_ups.is_in_shield_grace());
+
+        power_ups.tick(consts::SHIELD_GRACE_SECS / 2.);
+        assert!(power_ups.is_in_shield_grace());
+        power_ups.tick(consts::SHIELD_GRACE_SECS);
+        assert!(!power_ups.is_in_shield_grace());
+    }
+
+    #[test]
+    fn grace_end_is_not_reported_as_an_expired_power_up() {
+        let mut power_ups = PowerUpState::default();
+        power_ups.apply(FoodKind::Shield);
+        power_ups.take_shield();
+        assert!(power_ups.tick(consts::SHIELD_GRACE_SECS * 2.).is_empty());
+    }
+}
@@ -1,7 +1,14 @@
-use crate::game::{consts, food::Food, turn::TurnType};
-use crate::game::{coords::Coords, snake::Snake};
+use crate::game::segment::Renderable;
+use crate::game::{
+    consts,
+    direction::Direction,
+    food::{Food, FoodKind},
+    maths,
+    turn::TurnType,
+};
+use crate::game::{coords::Coords, snake::Snake, theme::ColorTheme};
 use ggez::{
-    graphics::{self, Color, FillOptions, Mesh, MeshBuilder, Text},
+    graphics::{self, Color, FillOptions, Mesh, MeshBuilder, Rect, Text},
     Context, GameError,
 };
 use graphics::Image;
@@ -12,47 +19,61 @@ use itertools as it;
 pub struct Renderer {}
 
 impl Renderer {
-    /// Draws a tiled background. Given image is scaled down to 50%
-    /// and tiled as needed, depending on the screen size.
+    /// Draws a tiled background, tinted with the theme background color.
+    /// Given image is scaled down to 50% and tiled as needed, depending on the screen size.
     ///
-    pub fn draw_bg(ctx: &mut Context, img: &Image) {
+    pub fn draw_bg(ctx: &mut Context, img: &Image, theme: &ColorTheme) {
+        let screen = graphics::screen_coordinates(ctx);
         let scale = 0.5;
         let (x_step, y_step) = {
             let dims = img.dimensions();
             ((dims.w * scale) as usize, (dims.h * scale) as usize)
         };
-        (0..consts::SCREEN_SIZE.y as i32)
-            .step_by(x_step)
-            .for_each(|yy| {
-                (0..consts::SCREEN_SIZE.x as i32)
-                    .step_by(y_step)
-                    .map(|xx| (xx as f32, yy as f32))
-                    .for_each(|(x, y)| {
-                        graphics::draw(
-                            ctx,
-                            img,
-                            graphics::DrawParam::new()
-                                .dest(Coords { x, y })
-                                .scale([scale, scale]),
-                        )
-                        .expect("Error while drawing background");
-                    })
-            });
+        (0..screen.h as i32).step_by(x_step).for_each(|yy| {
+            (0..screen.w as i32)
+                .step_by(y_step)
+                .map(|xx| (xx as f32, yy as f32))
+                .for_each(|(x, y)| {
+                    graphics::draw(
+                        ctx,
+                        img,
+                        graphics::DrawParam::new()
+                            .dest(Coords { x, y })
+                            .scale([scale, scale])
+                            .color(theme.background_color),
+                    )
+                    .expect("Error while drawing background");
+                })
+        });
     }
 
-    /// Draws a sprite on the position from the `Food` instance
+    /// Draws a sprite on the position from the `Food` instance, tinted with the food kind color,
+    /// or with the theme food color for `FoodKind::Normal`.
+    /// Sprite grows when the food spawns, fades out as the food gets older
+    /// and pulses around its center.
     ///
-    pub fn draw_food(ctx: &mut Context, food: &Food, img: &Image) {
-        let scalex = consts::FOOD_SIZE / img.dimensions().w;
-        let scaley = consts::FOOD_SIZE / img.dimensions().h;
+    pub fn draw_food(ctx: &mut Context, food: &Food, img: &Image, theme: &ColorTheme) {
+        let mut color = if food.kind == FoodKind::Normal {
+            theme.food_color
+        } else {
+            food.kind.color()
+        };
+        color.a = food.opacity();
+        let size = food.spawn_size() * food.pulse_scale();
+        let scalex = size.x / img.dimensions().w;
+        let scaley = size.y / img.dimensions().h;
         graphics::draw(
             ctx,
             img,
             graphics::DrawParam::new()
-                .dest(Coords::new(food.bbox.x, food.bbox.y))
-                .scale([scalex, scaley]),
+                .dest(food.center() - size / 2.)
+                .scale([scalex, scaley])
+                .color(color),
         )
         .expect("Error while drawing Food");
+        if theme.has_glow {
+            Self::draw_glow(ctx, food.bbox, color);
+        }
 
         #[cfg(feature = "debug")]
         {
@@ -68,23 +89,397 @@ impl Renderer {
         }
     }
 
-    /// Draws whole `LineSnake` structure
+    /// Draws a faint line between the snake head and the food pulled by the magnet
+    ///
+    pub fn draw_magnet_pull(ctx: &mut Context, head: Coords, food: Coords) {
+        if head.distance(&food) < consts::POINT_EPSILON {
+            return;
+        }
+        let color = Color {
+            a: 0.3,
+            ..FoodKind::Magnet.color()
+        };
+        let mesh = graphics::Mesh::new_line(ctx, &[head, food], 2., color).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing magnet pull");
+    }
+
+    /// Draws frozen food in `bbox` as covered with ice: a blue tint with a snowflake on top
+    ///
+    pub fn draw_frost(ctx: &mut Context, bbox: Rect) {
+        let center = Coords::new(bbox.x + bbox.w / 2., bbox.y + bbox.h / 2.);
+        let arm = bbox.w.min(bbox.h) * 0.4;
+        let mut builder = graphics::MeshBuilder::new();
+        builder.rectangle(
+            graphics::DrawMode::fill(),
+            bbox,
+            Color::new(0.5, 0.8, 1., 0.4),
+        );
+        // three crossing strokes make the six arms of the snowflake
+        for step in 0..3 {
+            let angle = step as f32 * std::f32::consts::FRAC_PI_3;
+            let offset = Coords::new(angle.cos(), angle.sin()) * arm;
+            builder
+                .line(&[center - offset, center + offset], 1.5, graphics::WHITE)
+                .expect("Error while building snowflake");
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing frost");
+    }
+
+    /// Draws faint outlines of the `consts::SNAKE_WIDTH` cells covering the screen
+    ///
+    pub fn draw_grid(ctx: &mut Context, screen: Coords) {
+        let mut builder = graphics::MeshBuilder::new();
+        let step = consts::SNAKE_WIDTH;
+        for row in 0..(screen.y / step).ceil() as usize {
+            for col in 0..(screen.x / step).ceil() as usize {
+                builder.rectangle(
+                    graphics::DrawMode::stroke(1.),
+                    Rect::new(col as f32 * step, row as f32 * step, step, step),
+                    Color::new(0.5, 0.5, 0.5, 0.3),
+                );
+            }
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing grid");
+    }
+
+    /// Draws a row of `capacity` squares starting at `pos`, the first `used` of them filled,
+    /// showing how many directions wait in the input queue
+    ///
+    pub fn draw_input_buffer(
+        ctx: &mut Context,
+        pos: Coords,
+        used: usize,
+        capacity: usize,
+        theme: &ColorTheme,
+    ) {
+        let size = consts::INPUT_SQUARE_SIZE;
+        let mut builder = graphics::MeshBuilder::new();
+        for idx in 0..capacity {
+            let square = Rect::new(pos.x + idx as f32 * size * 2., pos.y, size, size);
+            if idx < used {
+                builder.rectangle(graphics::DrawMode::fill(), square, theme.head_color);
+            } else {
+                builder.rectangle(graphics::DrawMode::stroke(1.), square, theme.text_color);
+            }
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing input buffer");
+    }
+
+    /// Draws a frame of `consts::DANGER_BORDER_WIDTH` along the screen edges
+    ///
+    pub fn draw_danger_border(ctx: &mut Context, screen: Coords, color: Color) {
+        let half = consts::DANGER_BORDER_WIDTH / 2.;
+        let mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(consts::DANGER_BORDER_WIDTH),
+            Rect::new(half, half, screen.x - 2. * half, screen.y - 2. * half),
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing danger border");
+    }
+
+    /// Draws a row of `capacity` hearts starting at `pos`, the first `filled` of them in red,
+    /// showing how many lives are left
+    ///
+    pub fn draw_hearts(
+        ctx: &mut Context,
+        pos: Coords,
+        filled: u32,
+        capacity: u32,
+        theme: &ColorTheme,
+    ) {
+        let size = consts::HEART_SIZE;
+        let radius = size / 4.;
+        let mut builder = graphics::MeshBuilder::new();
+        for idx in 0..capacity {
+            let left = pos.x + idx as f32 * size * 1.5;
+            let tip = [
+                Coords::new(left, pos.y + radius),
+                Coords::new(left + size, pos.y + radius),
+                Coords::new(left + size / 2., pos.y + size),
+            ];
+            let lobes = [
+                Coords::new(left + radius, pos.y + radius),
+                Coords::new(left + size - radius, pos.y + radius),
+            ];
+            // lost lives stay as faded hearts
+            let color = if idx < filled {
+                Color::from_rgb(230, 40, 60)
+            } else {
+                Color {
+                    a: 0.3,
+                    ..theme.text_color
+                }
+            };
+            for &lobe in &lobes {
+                builder.circle(graphics::DrawMode::fill(), lobe, radius, 0.2, color);
+            }
+            builder
+                .polygon(graphics::DrawMode::fill(), &tip, color)
+                .expect("Error while building heart");
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing hearts");
+    }
+
+    /// Draws the whole screen covered with `color`, used for flashes and tints
+    ///
+    pub fn draw_flash(ctx: &mut Context, screen: Coords, color: Color) {
+        let mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., 0., screen.x, screen.y),
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing flash");
+    }
+
+    /// Draws the deadly border areas as a translucent red overlay
+    ///
+    pub fn draw_hazard_zone(ctx: &mut Context, areas: &[Rect]) {
+        if areas.is_empty() {
+            return;
+        }
+
+        let mut builder = graphics::MeshBuilder::new();
+        for &area in areas {
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                area,
+                Color::new(0.9, 0.1, 0.1, 0.4),
+            );
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing hazard zone");
+    }
+
+    /// Draws `shape` in the given color, then, when the theme has glow,
+    /// translucent copies of its bounding box expanded by each of `consts::GLOW_PASSES`.
+    ///
+    pub fn draw_with_glow(
+        ctx: &mut Context,
+        shape: &dyn Renderable,
+        alpha: f32,
+        color: Color,
+        theme: &ColorTheme,
+    ) {
+        shape.draw(ctx, alpha, color);
+        if theme.has_glow {
+            Self::draw_glow(ctx, shape.bounding_box(), color);
+        }
+    }
+
+    /// Draws translucent copies of `area` in the given color,
+    /// expanded by each of `consts::GLOW_PASSES` with its opacity
+    ///
+    pub fn draw_glow(ctx: &mut Context, area: Rect, color: Color) {
+        let mut builder = MeshBuilder::new();
+        Self::add_glow(&mut builder, area, color);
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing glow");
+    }
+
+    /// Adds the glow passes of `draw_glow` to the mesh
+    ///
+    pub fn add_glow(builder: &mut MeshBuilder, area: Rect, color: Color) {
+        for &(expand, opacity) in consts::GLOW_PASSES.iter() {
+            let mut glow_color = color;
+            glow_color.a *= opacity;
+            let glow_area = Rect::new(
+                area.x - expand,
+                area.y - expand,
+                area.w + expand * 2.,
+                area.h + expand * 2.,
+            );
+            builder.rectangle(graphics::DrawMode::fill(), glow_area, glow_color);
+        }
+    }
+
+    /// Draws whole `LineSnake` structure, with colors fading from the head to the tail.
+    /// `alpha` tells how far the game is between the last and the next physics step.
     ///
-    pub fn draw_snake(ctx: &mut Context, snake: &Snake) {
+    /// Where a `Line` meets the next segment, a round cap is drawn over the joint,
+    /// so the body looks continuous.
+    ///
+    /// The whole body, with joints, is drawn as a single mesh, and so is its glow.
+    ///
+    pub fn draw_snake(
+        ctx: &mut Context,
+        snake: &Snake,
+        theme: &ColorTheme,
+        alpha: f32,
+        rainbow_hue: Option<f32>,
+    ) {
+        let total_segments = snake.body.len();
+        let mut body = MeshBuilder::new();
+        let mut glow = MeshBuilder::new();
+        for (idx, segment) in snake.body.iter().enumerate() {
+            let color = Self::snake_color(idx, total_segments, theme, rainbow_hue);
+            segment.add_to_mesh(&mut body, alpha, color);
+            if theme.has_glow {
+                Self::add_glow(&mut glow, segment.bounding_box(), color);
+            }
+            // head has no next segment to be joined with
+            if idx > 0 {
+                if let Some(pos) = segment.joint(alpha) {
+                    Self::add_joint(&mut body, pos, color);
+                }
+            }
+        }
+        let mut meshes = vec![body.build(ctx)];
+        if theme.has_glow {
+            meshes.push(glow.build(ctx));
+        }
+        for mesh in meshes.into_iter().flatten() {
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+                .expect("Error while drawing snake");
+        }
+
+        #[cfg(feature = "debug")]
         for segment in &snake.body {
-            segment.draw(ctx);
+            segment.draw_debug(ctx, alpha);
+        }
+
+        let head = snake.body.front().unwrap();
+        let (head_pos, head_dir) = (head.tip(alpha), head.direction());
+        Self::draw_head(ctx, head_pos, head_dir, theme.head_accent_color);
+        Self::draw_eyes(ctx, head_pos, head_dir);
+    }
+
+    /// Draws a ring of `consts::SHIELD_RADIUS` around the snake head at `pos`
+    ///
+    pub fn draw_shield(ctx: &mut Context, pos: Coords, color: Color) {
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(2.),
+            pos,
+            consts::SHIELD_RADIUS,
+            0.2,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing shield");
+    }
+
+    /// Returns the color of a snake segment, where index 0 is the head.
+    /// With `rainbow_hue` the segments go through all hues, starting from the given one,
+    /// otherwise they take the theme gradient colors.
+    ///
+    pub fn snake_color(
+        idx: usize,
+        total: usize,
+        theme: &ColorTheme,
+        rainbow_hue: Option<f32>,
+    ) -> Color {
+        match rainbow_hue {
+            Some(offset) => {
+                let hue = idx as f32 / total.max(1) as f32 * 360. + offset;
+                let (r, g, b) = maths::hsl_to_rgb(hue, 1., 0.5);
+                Color::new(r, g, b, theme.head_color.a)
+            }
+            None => theme.segment_color(idx, total),
         }
     }
 
-    /// Draws given text in a white color with a black outline
+    /// Draws the snake head on `pos`, pointed towards `dir`.
+    /// Head is a bit wider than the body, but it is only drawn that way,
+    /// collisions still use the segment bounding boxes.
+    ///
+    pub fn draw_head(ctx: &mut Context, pos: Coords, dir: Direction, color: Color) {
+        let front = dir.as_coords() * consts::HEAD_HALF_WIDTH;
+        let side = Coords::new(front.y, -front.x);
+        // pointed end lands exactly on `pos`
+        let points = [
+            pos - front * 3. + side,
+            pos - front + side,
+            pos,
+            pos - front - side,
+            pos - front * 3. - side,
+        ];
+        let mesh = Mesh::new_polygon(ctx, graphics::DrawMode::fill(), &points, color).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing head");
+    }
+
+    /// Draws two eyes on the head that ends on `head_pos`,
+    /// placed on both sides of the `dir` line and looking forward.
+    ///
+    pub fn draw_eyes(ctx: &mut Context, head_pos: Coords, dir: Direction) {
+        let front = dir.as_coords();
+        let side = Coords::new(front.y, -front.x) * consts::HEAD_HALF_WIDTH * 0.5;
+        let center = head_pos - front * consts::HEAD_HALF_WIDTH * 1.5;
+
+        let mut builder = MeshBuilder::new();
+        for eye in [center + side, center - side].iter() {
+            builder
+                .circle(
+                    graphics::DrawMode::fill(),
+                    *eye,
+                    consts::EYE_RADIUS,
+                    0.2,
+                    graphics::WHITE,
+                )
+                .circle(
+                    graphics::DrawMode::fill(),
+                    *eye + front * (consts::EYE_RADIUS - consts::PUPIL_RADIUS),
+                    consts::PUPIL_RADIUS,
+                    0.2,
+                    graphics::BLACK,
+                );
+        }
+        let mesh = builder.build(ctx).unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing eyes");
+    }
+
+    /// Adds a filled circle of the snake width centered on `pos` to the mesh
+    ///
+    pub fn add_joint(builder: &mut MeshBuilder, pos: Coords, color: Color) {
+        builder.circle(
+            graphics::DrawMode::fill(),
+            pos,
+            consts::SNAKE_HALF_WIDTH,
+            0.5,
+            color,
+        );
+    }
+
+    /// Draws given text in the theme text color with a black outline
     ///
     /// # Parameters
     ///
     /// - `ctx`: game context
     /// - `txt`: the text itself
     /// - `pos`: position of the top left corner of the text
+    /// - `theme`: colors of the game
     ///
-    pub fn draw_text_with_outline(ctx: &mut Context, txt: &Text, pos: Coords) {
+    pub fn draw_text_with_outline(ctx: &mut Context, txt: &Text, pos: Coords, theme: &ColorTheme) {
+        Self::draw_colored_text_with_outline(ctx, txt, pos, theme.text_color);
+    }
+
+    /// Draws given text in the given color with a black outline
+    ///
+    pub fn draw_colored_text_with_outline(
+        ctx: &mut Context,
+        txt: &Text,
+        pos: Coords,
+        color: Color,
+    ) {
         const WIDTH: f32 = 2.;
         [-WIDTH, 0., WIDTH].iter().for_each(|x| {
             [-WIDTH, 0., WIDTH].iter().for_each(|y| {
@@ -99,7 +494,42 @@ impl Renderer {
         });
 
         let params = graphics::DrawParam::default().dest(pos);
-        graphics::draw(ctx, txt, params.color(graphics::WHITE)).expect("Error while drawing score");
+        graphics::draw(ctx, txt, params.color(color)).expect("Error while drawing score");
+    }
+
+    /// Draws given text in the theme text color with an outline, centered on the screen
+    /// and moved vertically by `y_offset`
+    ///
+    pub fn draw_centered_text_with_outline(
+        ctx: &mut Context,
+        txt: &Text,
+        y_offset: f32,
+        theme: &ColorTheme,
+    ) {
+        Self::draw_centered_colored_text_with_outline(ctx, txt, y_offset, theme.text_color);
+    }
+
+    /// Draws given text in the given color with an outline, centered on the screen
+    /// and moved vertically by `y_offset`
+    ///
+    pub fn draw_centered_colored_text_with_outline(
+        ctx: &mut Context,
+        txt: &Text,
+        y_offset: f32,
+        color: Color,
+    ) {
+        let screen = graphics::screen_coordinates(ctx);
+        let (xdim, ydim) = txt.dimensions(ctx);
+        let (xdim, ydim) = (xdim as f32, ydim as f32);
+        Self::draw_colored_text_with_outline(
+            ctx,
+            txt,
+            Coords::new(
+                screen.w / 2. - xdim / 2.,
+                screen.h / 2. - ydim / 2. + y_offset,
+            ),
+            color,
+        );
     }
 
     /// Draws given text in a black color
@@ -133,12 +563,12 @@ impl Renderer {
         points
     }
 
-    /// Creates mesh of a quater of the ring.
+    /// Adds a quater of the ring to the mesh.
     /// How much part is drawn and the starting edge can be chosen via params.
     ///
     /// # Parameters
     ///
-    /// - `ctx`: game context
+    /// - `builder`: mesh the ring is added to
     /// - `pos`: position where middle of the mesh should be
     /// - `r1`: radius of the outer edge of the ring, must be > 0
     /// - `r2`: radius of the inner edge of the ring, must be >= 0
@@ -146,16 +576,18 @@ impl Renderer {
     /// - `progress`: how much of a quater should be created, between 0 and 1
     /// - `reversed`: determines from which end the progress is
     /// - `is_head`: whether the segment drawn is a head and we want to draw eyes
+    /// - `color`: fill color of the ring
     ///
     /// # Returns
     ///
     /// A `Result` which is:
     ///
-    /// - `Ok`: A `Mesh` that is ready to be drawn
+    /// - `Ok`: The ring was added to `builder`
     /// - `Err`: Something went wrong I guess...or You gave a negative radius
     ///
-    pub fn create_qt_ring(
-        ctx: &mut Context,
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_qt_ring(
+        builder: &mut MeshBuilder,
         pos: Coords,
         r1: f32,
         r2: f32,
@@ -163,7 +595,8 @@ impl Renderer {
         progress: f32,
         reversed: bool,
         is_head: bool,
-    ) -> Result<Mesh, GameError> {
+        color: Color,
+    ) -> Result<(), GameError> {
         if r1 <= 0. || r2 < 0. {
             return Result::Err(GameError::ConfigError(
                 "Radiuses cannot be negative!".to_string(),
@@ -183,9 +616,9 @@ impl Renderer {
         let polys = it::chain(outers, it::rev(inners)).collect();
 
         if is_head {
-            create_head(polys, ctx)
+            add_head(builder, polys, color)
         } else {
-            create_body(polys, ctx)
+            add_body(builder, polys, color)
         }
     }
 
@@ -199,35 +632,40 @@ impl Renderer {
     }
 }
 
-fn create_body(polys: Vec<Coords>, ctx: &mut Context) -> Result<Mesh, ggez::GameError> {
-    MeshBuilder::new()
-        .polygon(
-            graphics::DrawMode::Fill(FillOptions::default()),
-            &polys,
-            Color::from_rgb(255, 255, 0),
-        )
-        .unwrap()
-        .build(ctx)
+fn add_body(
+    builder: &mut MeshBuilder,
+    polys: Vec<Coords>,
+    color: Color,
+) -> Result<(), ggez::GameError> {
+    builder.polygon(
+        graphics::DrawMode::Fill(FillOptions::default()),
+        &polys,
+        color,
+    )?;
+    Ok(())
 }
 
-fn create_head(polys: Vec<Coords>, ctx: &mut Context) -> Result<Mesh, ggez::GameError> {
+fn add_head(
+    builder: &mut MeshBuilder,
+    polys: Vec<Coords>,
+    color: Color,
+) -> Result<(), ggez::GameError> {
     let pt1 = polys.first().unwrap();
     let pt2 = polys.last().unwrap();
 
     let vect = *pt2 - *pt1;
-    let len = (vect.x.powi(2) + vect.y.powi(2)).sqrt();
-    let norm = Coords::new(vect.x / len, vect.y / len);
+    let len = vect.magnitude();
+    let norm = vect.normalize();
     let ppd = Coords::new(norm.y, -norm.x);
     let eye1 = *pt1 + (vect * 0.25 * len) + (ppd * 0.25 * len);
     let eye2 = *pt1 + (vect * 0.75 * len) + (ppd * 0.25 * len);
 
-    MeshBuilder::new()
+    builder
         .polygon(
             graphics::DrawMode::Fill(FillOptions::default()),
             &polys,
-            Color::from_rgb(255, 255, 0),
-        )
-        .unwrap()
+            color,
+        )?
         .circle(
             graphics::DrawMode::Fill(FillOptions::default()),
             eye1,
@@ -241,6 +679,44 @@ fn create_head(polys: Vec<Coords>, ctx: &mut Context) -> Result<Mesh, ggez::Game
             10.,
             1.,
             graphics::BLACK,
-        )
-        .build(ctx)
+        );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    #[ignore = "timing, run with `cargo test --release -- --ignored`"]
+    fn glow_of_a_snake_filling_the_default_board_fits_in_a_frame() {
+        const RUNS: u32 = 20;
+        // a tenth of a frame at 60 fps, leaving the rest for drawing
+        const BUDGET: Duration = Duration::from_micros(1_666);
+
+        let cols = (consts::SCREEN_SIZE.x / consts::SNAKE_WIDTH) as i32;
+        let rows = (consts::SCREEN_SIZE.y / consts::SNAKE_WIDTH) as i32;
+        let cells: Vec<Rect> = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                Rect::new(
+                    x as f32 * consts::SNAKE_WIDTH,
+                    y as f32 * consts::SNAKE_WIDTH,
+                    consts::SNAKE_WIDTH,
+                    consts::SNAKE_WIDTH,
+                )
+            })
+            .collect();
+
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            let mut glow = MeshBuilder::new();
+            for &cell in &cells {
+                Renderer::add_glow(&mut glow, cell, graphics::WHITE);
+            }
+        }
+        let per_frame = start.elapsed() / RUNS;
+        assert!(per_frame < BUDGET, "took {:?}", per_frame);
+    }
 }
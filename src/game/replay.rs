@@ -0,0 +1,120 @@
+//! Module with replay recording and playback
+use ggez::{GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::game::{consts, difficulty::DifficultyLevel, direction::Direction};
+
+/// Everything that is needed to play a recorded run once again
+///
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: DifficultyLevel,
+    pub wrap_walls: bool,
+    pub entries: Vec<(f32, Direction)>,
+}
+
+impl Replay {
+    /// Read a replay from the given `.replay` file
+    ///
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            GameError::FilesystemError(format!("Could not read {}: {}", path.display(), err))
+        })?;
+        serde_json::from_str(&content).map_err(|err| {
+            GameError::FilesystemError(format!("Malformed replay {}: {}", path.display(), err))
+        })
+    }
+
+    /// Write the replay into the given file, creating missing directories
+    ///
+    pub fn save(&self, path: &Path) -> GameResult {
+        let content = serde_json::to_string(self)
+            .map_err(|err| GameError::FilesystemError(err.to_string()))?;
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, content))
+            .map_err(|err| {
+                GameError::FilesystemError(format!("Could not write {}: {}", path.display(), err))
+            })
+    }
+
+    /// Return the path where the last recorded run is kept
+    ///
+    pub fn last_replay_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::LAST_REPLAY_FILE))
+    }
+}
+
+/// Structure that collects every direction change of a run, along with the time it happened
+///
+pub struct ReplayRecorder {
+    pub seed: u64,
+    pub difficulty: DifficultyLevel,
+    pub wrap_walls: bool,
+    pub entries: Vec<(f32, Direction)>,
+}
+
+impl ReplayRecorder {
+    /// Start recording a run that uses given settings
+    ///
+    pub fn new(seed: u64, difficulty: DifficultyLevel, wrap_walls: bool) -> Self {
+        Self {
+            seed,
+            difficulty,
+            wrap_walls,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Store the direction change that happened `time` seconds after the start of the run
+    ///
+    pub fn record(&mut self, time: f32, dir: Direction) {
+        self.entries.push((time, dir));
+    }
+
+    /// Finish recording and return the complete `Replay`
+    ///
+    pub fn into_replay(self) -> Replay {
+        Replay {
+            seed: self.seed,
+            difficulty: self.difficulty,
+            wrap_walls: self.wrap_walls,
+            entries: self.entries,
+        }
+    }
+}
+
+/// Structure that feeds recorded direction changes back in order
+///
+pub struct ReplayPlayer {
+    pub entries: Vec<(f32, Direction)>,
+    pub next: usize,
+}
+
+impl ReplayPlayer {
+    /// Prepare playback of the given replay from its beginning
+    ///
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            entries: replay.entries,
+            next: 0,
+        }
+    }
+
+    /// Return the next recorded direction, if it should already be applied at `time`
+    ///
+    pub fn poll(&mut self, time: f32) -> Option<Direction> {
+        let &(entry_time, dir) = self.entries.get(self.next)?;
+        if entry_time > time {
+            return None;
+        }
+
+        self.next += 1;
+        Some(dir)
+    }
+}
@@ -1,7 +1,20 @@
 use ggez::{
+    audio::{SoundSource, Source},
     graphics::{Font, Image},
-    Context,
+    Context, GameError,
 };
+use std::{fs, path::Path};
+
+use crate::game::event::{GameEvent, GameEventListener};
+use crate::game::{consts, i18n::Strings};
+
+/// Enumeration of the background music tracks
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MusicTrack {
+    Menu,
+    Game,
+}
 
 /// Structure for loading and storing all resources need for the game.
 ///
@@ -9,16 +22,113 @@ pub struct ResourceLoader {
     pub bg_image: Image,
     pub food_image: Image,
     pub font: Font,
+    pub eat_sound: Source,
+    pub die_sound: Source,
+    pub turn_sound: Source,
+    pub music: Source,
+    pub menu_music: Source,
 }
 
 impl ResourceLoader {
     /// Load all resources and create new instance with them.
+    /// Music tracks are set to loop.
+    ///
+    /// Fails on the first resource that is missing or cannot be decoded.
+    ///
+    pub fn new(ctx: &mut Context) -> Result<Self, GameError> {
+        let mut music = Source::new(ctx, "/music.wav")?;
+        music.set_repeat(true);
+        let mut menu_music = Source::new(ctx, "/menu_music.wav")?;
+        menu_music.set_repeat(true);
+        Ok(Self {
+            bg_image: Image::new(ctx, "/grass.png")?,
+            food_image: Image::new(ctx, "/ball.png")?,
+            font: Font::new(ctx, "/Roboto-Black.ttf")?,
+            eat_sound: Source::new(ctx, "/eat.wav")?,
+            die_sound: Source::new(ctx, "/die.wav")?,
+            turn_sound: Source::new(ctx, "/turn.wav")?,
+            music,
+            menu_music,
+        })
+    }
+
+    /// Load a TTF font from any path on the disk, outside of the resource directories.
+    /// Font data is checked before it is handed over to ggez, which would panic on a malformed one.
+    ///
+    pub fn load_custom_font(ctx: &mut Context, path: &Path) -> Result<Font, GameError> {
+        let bytes = fs::read(path).map_err(|err| {
+            GameError::FilesystemError(format!("Could not read {}: {}", path.display(), err))
+        })?;
+        rusttype::Font::from_bytes(bytes.as_slice()).map_err(|err| {
+            GameError::ResourceLoadError(format!("Could not load font {}: {}", path.display(), err))
+        })?;
+        Font::new_glyph_font_bytes(ctx, &bytes)
+    }
+
+    /// Read all texts in the given language from `consts::STRINGS_DIR`.
+    /// Missing or malformed language falls back to English, reporting the error.
+    ///
+    pub fn load_strings(lang: &str) -> Strings {
+        let read = |lang: &str| -> Result<Strings, String> {
+            let path = Path::new(consts::STRINGS_DIR).join(format!("{}.toml", lang));
+            let content = fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+            toml::from_str(&content)
+                .map_err(|err| format!("Could not parse {}: {}", path.display(), err))
+        };
+        read(lang).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            if lang == consts::DEFAULT_LANGUAGE {
+                return Strings::default();
+            }
+            read(consts::DEFAULT_LANGUAGE).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                Strings::default()
+            })
+        })
+    }
+
+    /// Return the source playing given music track
     ///
-    pub fn new(ctx: &mut Context) -> Self {
-        Self {
-            bg_image: Image::new(ctx, "/grass.png").unwrap(),
-            food_image: Image::new(ctx, "/ball.png").unwrap(),
-            font: Font::new(ctx, "/Roboto-Black.ttf").unwrap(),
+    pub fn music_source(&mut self, track: MusicTrack) -> &mut Source {
+        match track {
+            MusicTrack::Menu => &mut self.menu_music,
+            MusicTrack::Game => &mut self.music,
         }
     }
+
+    /// Set volume of all sound effects, between 0 and 1.
+    ///
+    pub fn set_sfx_volume(&mut self, volume: f32) {
+        for sound in [
+            &mut self.eat_sound,
+            &mut self.die_sound,
+            &mut self.turn_sound,
+        ] {
+            sound.set_volume(volume);
+        }
+    }
+}
+
+impl GameEventListener for ResourceLoader {
+    /// Plays the sound effect of eating and dying
+    ///
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::FoodEaten { .. } => play_sound(&mut self.eat_sound),
+            GameEvent::SnakeDied { .. } => play_sound(&mut self.die_sound),
+            GameEvent::LevelComplete
+            | GameEvent::PowerUpExpired { .. }
+            | GameEvent::StepFinished { .. } => {}
+        }
+    }
+}
+
+/// Play the sound without cutting off its previous playback, which may still last.
+/// Playback errors are only reported, as the game works fine without sound.
+///
+pub fn play_sound(sound: &mut Source) {
+    if let Err(err) = sound.play_detached() {
+        eprintln!("{}", err);
+    }
 }
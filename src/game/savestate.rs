@@ -0,0 +1,141 @@
+//! Module with SaveState struct, a snapshot of the run that can be resumed later
+use ggez::{graphics::Rect, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::game::state::{GameMode, ShrinkBorder, SurvivalState};
+use crate::game::{consts, difficulty::DifficultyLevel, direction::Direction, food::Food};
+use crate::game::{line::Line, powerup::PowerUpState, segment::Segment, snake::Snake, turn::Turn};
+
+/// Mirror of `ggez::graphics::Rect`, so rectangles can be serialized as a part of other types
+///
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Rect")]
+pub struct RectDef {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// Copy of a single snake segment, with the kind of the segment kept
+///
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum SegmentSnapshot {
+    Line(Line),
+    Turn(Turn),
+}
+
+impl SegmentSnapshot {
+    /// Turn the copy back into a segment that the snake is built of
+    ///
+    pub fn into_segment(self) -> Box<dyn Segment> {
+        match self {
+            SegmentSnapshot::Line(line) => Box::new(line),
+            SegmentSnapshot::Turn(turn) => Box::new(turn),
+        }
+    }
+}
+
+/// Copy of the whole snake
+///
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SnakeSnapshot {
+    pub body: Vec<SegmentSnapshot>,
+    pub dir: Direction,
+}
+
+impl From<&Snake> for SnakeSnapshot {
+    fn from(snake: &Snake) -> Self {
+        Self {
+            body: snake
+                .body
+                .iter()
+                .map(|segment| segment.snapshot())
+                .collect(),
+            dir: snake.dir,
+        }
+    }
+}
+
+impl From<SnakeSnapshot> for Snake {
+    fn from(snapshot: SnakeSnapshot) -> Self {
+        Self {
+            body: snapshot
+                .body
+                .into_iter()
+                .map(SegmentSnapshot::into_segment)
+                .collect(),
+            dir: snapshot.dir,
+        }
+    }
+}
+
+/// Everything that is needed to resume a run from the moment it was saved.
+///
+/// Obstacles, portals and terrain are not stored, they are loaded again
+/// from the level, and the maze is generated again from `seed`.
+/// Random number generator starts over from `seed` as well,
+/// so food eaten after resuming is placed differently than in the original run.
+///
+#[derive(Serialize, Deserialize)]
+pub struct SaveState {
+    pub mode: GameMode,
+    pub difficulty: DifficultyLevel,
+    pub wrap_walls: bool,
+    pub two_player: bool,
+    pub seed: u64,
+    pub current_level: u32,
+    pub snake: SnakeSnapshot,
+    pub snake2: Option<SnakeSnapshot>,
+    pub foods: Vec<Food>,
+    pub score: u32,
+    pub score2: u32,
+    pub combo: u32,
+    pub combo_timer: f32,
+    pub inputs: VecDeque<Direction>,
+    pub inputs2: VecDeque<Direction>,
+    pub input_timer: f32,
+    pub input_timer2: f32,
+    pub power_ups: PowerUpState,
+    pub hunger_timer: f32,
+    pub lives: u32,
+    pub remaining_time: f32,
+    pub survival: SurvivalState,
+    pub shrink_border: ShrinkBorder,
+    pub run_time: f32,
+    pub max_length: f32,
+    pub total_moves: u64,
+    pub total_turns: u64,
+}
+
+impl SaveState {
+    /// Read the raw save from the given file
+    ///
+    pub fn read(path: &Path) -> GameResult<Vec<u8>> {
+        fs::read(path).map_err(|err| {
+            GameError::FilesystemError(format!("Could not read {}: {}", path.display(), err))
+        })
+    }
+
+    /// Write the raw save into the given file, creating missing directories
+    ///
+    pub fn write(path: &Path, data: &[u8]) -> GameResult {
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, data))
+            .map_err(|err| {
+                GameError::FilesystemError(format!("Could not write {}: {}", path.display(), err))
+            })
+    }
+
+    /// Return the path where the automatic save is kept
+    ///
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::SAVE_FILE))
+    }
+}
@@ -1,22 +1,83 @@
-use ggez::{graphics::Rect, Context};
+use ggez::{
+    graphics::{self, Color, MeshBuilder, Rect},
+    Context,
+};
 
-use super::{consts, coords::Coords, direction::Direction};
+use super::{consts, coords::Coords, direction::Direction, savestate::SegmentSnapshot};
 
 /// Trait for growth functionality of the snake segments
 ///
 pub trait Growable {
     fn grow(&mut self, dist: f32) -> f32;
     fn shrink(&mut self, dist: f32) -> f32;
+    fn size(&self) -> f32;
+
+    /// Return the position of the shrinking end
+    ///
+    fn beg(&self) -> Coords;
     fn end(&self) -> Coords;
     fn direction(&self) -> Direction;
+    fn shift(&mut self, offset: Coords);
+    fn store_previous(&mut self);
+
+    /// Return a copy of the segment that can be written into a save file
+    ///
+    fn snapshot(&self) -> SegmentSnapshot;
 }
 
-/// Trait for rendering and collision functionality of the snake segments
+/// Trait for rendering and collision functionality of the snake segments.
+///
+/// `draw` gets `alpha`, between 0 and 1, telling how far the game is
+/// between the last and the next physics step. Segments are drawn that far
+/// between their previous and current positions, filled with `color`.
+///
+/// Shapes are added to a `MeshBuilder` by `add_to_mesh`, so many of them
+/// can be drawn with a single draw call. `draw` draws just this one.
 ///
 pub trait Renderable {
-    fn draw(&self, ctx: &mut Context);
+    fn add_to_mesh(&self, builder: &mut MeshBuilder, alpha: f32, color: Color);
     fn bounding_box(&self) -> Rect;
 
+    /// Return the area the shape covers, taken from its bounding box by default
+    ///
+    fn area(&self) -> f32 {
+        let bbox = self.bounding_box();
+        bbox.w * bbox.h
+    }
+
+    /// Return the layer the shape is drawn in, higher layers are drawn on top of lower ones
+    ///
+    fn z_order(&self) -> i32 {
+        consts::Z_WORLD
+    }
+
+    /// Draws the outline of the bounding box, used only with the `debug` feature
+    ///
+    #[cfg_attr(not(feature = "debug"), allow(dead_code))]
+    fn draw_debug(&self, _ctx: &mut Context, _alpha: f32) {}
+
+    fn draw(&self, ctx: &mut Context, alpha: f32, color: Color) {
+        let mut builder = MeshBuilder::new();
+        self.add_to_mesh(&mut builder, alpha, color);
+        if let Ok(mesh) = builder.build(ctx) {
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+                .expect("Error while drawing segment");
+        }
+
+        #[cfg(feature = "debug")]
+        self.draw_debug(ctx, alpha);
+    }
+
+    /// Return the position of the growing end, as it is drawn
+    ///
+    fn tip(&self, alpha: f32) -> Coords;
+
+    /// Return where the next segment is attached, if a joint cap should be drawn there
+    ///
+    fn joint(&self, _alpha: f32) -> Option<Coords> {
+        None
+    }
+
     fn collision(&self, other: &Rect) -> bool {
         let rect = self.bounding_box();
 
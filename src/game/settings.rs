@@ -0,0 +1,216 @@
+//! Module with SettingsMenu struct, managing the settings screen
+use ggez::{
+    graphics::{self, Font, Text, TextFragment},
+    Context,
+};
+
+use crate::game::config::{ControlScheme, GameConfig};
+use crate::game::{
+    consts,
+    difficulty::DifficultyLevel,
+    i18n::{self, Strings},
+    input::{self, KeyBindings},
+    menu,
+    renderer::Renderer,
+    theme::{ColorTheme, ThemeName},
+};
+
+/// Enumeration of the options on the settings screen, in the order they are shown
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SettingItem {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Theme,
+    Controls,
+    Difficulty,
+}
+
+impl SettingItem {
+    /// Returns all items, in the order they are shown
+    ///
+    pub fn all() -> [SettingItem; 6] {
+        [
+            SettingItem::MasterVolume,
+            SettingItem::MusicVolume,
+            SettingItem::SfxVolume,
+            SettingItem::Theme,
+            SettingItem::Controls,
+            SettingItem::Difficulty,
+        ]
+    }
+
+    /// Returns the row shown for the item, with its current value from `config`
+    ///
+    pub fn label(&self, config: &GameConfig, strings: &Strings) -> String {
+        let percent = |volume: f32| format!("{:.0}%", volume * 100.);
+        match self {
+            SettingItem::MasterVolume => format!(
+                SETTING_FMT!(),
+                strings.master_volume,
+                percent(config.master_volume)
+            ),
+            SettingItem::MusicVolume => format!(
+                SETTING_FMT!(),
+                strings.music_volume,
+                percent(config.music_volume)
+            ),
+            SettingItem::SfxVolume => {
+                format!(
+                    SETTING_FMT!(),
+                    strings.sfx_volume,
+                    percent(config.sfx_volume)
+                )
+            }
+            SettingItem::Theme => format!(SETTING_FMT!(), strings.theme, config.theme),
+            SettingItem::Controls => {
+                format!(SETTING_FMT!(), strings.controls, config.control_scheme)
+            }
+            SettingItem::Difficulty => {
+                format!(SETTING_FMT!(), strings.difficulty, config.difficulty)
+            }
+        }
+    }
+
+    /// Changes the value of the item in `config` by `steps`,
+    /// negative steps go back. Volumes stay between 0 and 1,
+    /// other values wrap around.
+    ///
+    pub fn change(&self, config: &mut GameConfig, steps: i32) {
+        let volume = |value: f32| f32::clamp(value + steps as f32 * consts::VOLUME_STEP, 0., 1.);
+        match self {
+            SettingItem::MasterVolume => config.master_volume = volume(config.master_volume),
+            SettingItem::MusicVolume => config.music_volume = volume(config.music_volume),
+            SettingItem::SfxVolume => config.sfx_volume = volume(config.sfx_volume),
+            SettingItem::Theme => config.theme = cycle(&ThemeName::all(), config.theme, steps),
+            SettingItem::Controls => {
+                config.control_scheme = cycle(&ControlScheme::all(), config.control_scheme, steps)
+            }
+            SettingItem::Difficulty => {
+                config.difficulty = cycle(&DifficultyLevel::all(), config.difficulty, steps)
+            }
+        }
+    }
+}
+
+/// Returns the value `steps` positions away from `current` in `all`, wrapping around
+///
+fn cycle<T: PartialEq + Copy>(all: &[T], current: T, steps: i32) -> T {
+    let len = all.len() as i32;
+    let idx = all.iter().position(|&value| value == current).unwrap_or(0) as i32;
+    all[(idx + steps).rem_euclid(len) as usize]
+}
+
+/// Structure for holding the settings screen texts and the selected item
+///
+pub struct SettingsMenu {
+    pub selected: usize,
+    pub title_txt: Text,
+    pub back_txt: Text,
+    pub export_txt: Text,
+}
+
+impl SettingsMenu {
+    /// Creates new `SettingsMenu` with the first item selected.
+    ///
+    pub fn new(strings: &Strings, keys: &KeyBindings, font: Font) -> Self {
+        Self {
+            selected: 0,
+            title_txt: Text::new(
+                TextFragment::new(strings.settings_title.as_str())
+                    .scale(graphics::Scale::uniform(64.))
+                    .font(font),
+            ),
+            back_txt: Self::create_back_txt(strings, keys, font),
+            export_txt: Self::create_export_txt(strings, keys, font),
+        }
+    }
+
+    /// Creates the hint about the keys used on the settings screen
+    ///
+    pub fn create_back_txt(strings: &Strings, keys: &KeyBindings, font: Font) -> Text {
+        let hint = i18n::fill(
+            &strings.settings_hint,
+            &[
+                &input::key_name(&keys.up),
+                &input::key_name(&keys.down),
+                &input::key_name(&keys.left),
+                &input::key_name(&keys.right),
+                &input::key_name(&keys.pause),
+            ],
+        );
+        Text::new(
+            TextFragment::new(hint)
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+
+    /// Creates the hint about the key that exports the colors in use
+    ///
+    pub fn create_export_txt(strings: &Strings, keys: &KeyBindings, font: Font) -> Text {
+        let hint = i18n::fill(
+            &strings.settings_export_hint,
+            &[&input::key_name(&keys.export_palette)],
+        );
+        Text::new(
+            TextFragment::new(hint)
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+
+    /// Returns the currently selected item
+    ///
+    pub fn selected_item(&self) -> SettingItem {
+        SettingItem::all()[self.selected]
+    }
+
+    /// Moves the selection to the previous item, wrapping around
+    ///
+    pub fn select_prev(&mut self) {
+        let len = SettingItem::all().len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    /// Moves the selection to the next item, wrapping around
+    ///
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % SettingItem::all().len();
+    }
+
+    /// Draws the title and a row for each item with its value in `config`,
+    /// with the selected row highlighted.
+    ///
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        config: &GameConfig,
+        strings: &Strings,
+        theme: &ColorTheme,
+        font: Font,
+    ) {
+        Renderer::draw_centered_text_with_outline(ctx, &self.title_txt, -240., theme);
+        for (idx, item) in SettingItem::all().iter().enumerate() {
+            let txt = Text::new(
+                TextFragment::new(item.label(config, strings))
+                    .scale(graphics::Scale::uniform(32.))
+                    .font(font),
+            );
+            let color = if idx == self.selected {
+                menu::HIGHLIGHT_COLOR
+            } else {
+                theme.text_color
+            };
+            Renderer::draw_centered_colored_text_with_outline(
+                ctx,
+                &txt,
+                -140. + idx as f32 * 50.,
+                color,
+            );
+        }
+        Renderer::draw_centered_text_with_outline(ctx, &self.back_txt, 200., theme);
+        Renderer::draw_centered_text_with_outline(ctx, &self.export_txt, 240., theme);
+    }
+}
@@ -0,0 +1,501 @@
+//! Module with Simulation struct, playing the classic game without a window
+use ggez::graphics::Rect;
+use itertools::{self as it, Itertools};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{collections::VecDeque, fmt, time::Instant};
+
+use crate::game::spatial::SpatialHash;
+use crate::game::{ai::AiController, config::GameConfig, consts, coords::Coords};
+use crate::game::{death::DeathCause, direction::Direction, level::Level, obstacle::Obstacle};
+use crate::game::{food::Food, food::FoodKind, powerup::PowerUpState, snake::Snake};
+
+/// Outcome of a single `Simulation::tick`
+///
+#[derive(PartialEq, Clone, Debug)]
+pub enum SimResult {
+    /// Snake is still alive
+    Running,
+    /// Snake ate food and got the given number of points
+    Ate(u32),
+    /// Run has ended for the given reason
+    GameOver(DeathCause),
+}
+
+/// Structure that plays the rules of `GameMode::Classic` on the first level,
+/// without sounds, effects or anything that needs a ggez context.
+///
+/// Every tick advances the game by `consts::SECS_PER_PHYSICS_STEP`,
+/// so runs are as fast as the machine allows and the same seed gives the same run.
+///
+pub struct Simulation {
+    pub screen: Coords,
+    pub snake: Snake,
+    pub foods: Vec<Food>,
+    pub obstacles: Vec<Obstacle>,
+    pub inputs: VecDeque<Direction>,
+    pub input_timer: f32,
+    pub score: u32,
+    pub combo: u32,
+    pub combo_timer: f32,
+    pub power_ups: PowerUpState,
+    pub hunger_timer: f32,
+    pub run_time: f32,
+    pub max_food: usize,
+    pub speed: f32,
+    pub secs_per_input_update: f32,
+    pub config: GameConfig,
+    pub spatial_hash: SpatialHash,
+    pub rng: SmallRng,
+}
+
+impl Simulation {
+    /// Create new `Simulation` with the snake in the middle of the screen,
+    /// moving with the speed of the difficulty from `config`
+    ///
+    pub fn new(config: &GameConfig, seed: u64) -> Self {
+        let screen = Coords::new(config.screen_width, config.screen_height);
+        let level = Level::fallback(config.max_food);
+        let multiplier = config.difficulty.speed_multiplier();
+        let mut sim = Self {
+            screen,
+            snake: Snake::new(screen.x / 2., screen.y / 2.),
+            foods: Vec::new(),
+            obstacles: level.obstacles,
+            inputs: VecDeque::new(),
+            input_timer: 0.,
+            score: 0,
+            combo: 0,
+            combo_timer: 0.,
+            power_ups: PowerUpState::default(),
+            hunger_timer: config.hunger_rate,
+            run_time: 0.,
+            max_food: level.food_count,
+            speed: config.snake_speed * multiplier,
+            secs_per_input_update: config.secs_per_input_update / multiplier,
+            config: config.clone(),
+            spatial_hash: SpatialHash::default(),
+            rng: SmallRng::seed_from_u64(seed),
+        };
+        sim.refill_food();
+        sim
+    }
+
+    /// Advance the game by one physics step, turning the snake towards `input` when given.
+    /// Turns are capped to `GameConfig::secs_per_input_update`, just like in the game.
+    ///
+    pub fn tick(&mut self, input: Option<Direction>) -> SimResult {
+        let dt = consts::SECS_PER_PHYSICS_STEP;
+        self.run_time += dt;
+        let has_room = self.inputs.len() < self.config.max_input_queue;
+        if let Some(dir) = input.filter(|&dir| has_room && self.inputs.back() != Some(&dir)) {
+            self.inputs.push_front(dir);
+        }
+        self.input_timer += dt;
+        if self.input_timer >= self.secs_per_input_update
+            && apply_queued_turn(&mut self.inputs, &mut self.snake).is_some()
+        {
+            self.input_timer = 0.;
+        }
+
+        if self.combo > 0 {
+            self.combo_timer = f32::max(self.combo_timer - dt, 0.);
+            if self.combo_timer <= 0. {
+                self.combo = 0;
+            }
+        }
+        let frozen = self.power_ups.is_frozen();
+        let hunger_dt = if frozen { 0. } else { dt };
+        if starve(
+            &mut self.snake,
+            &mut self.hunger_timer,
+            self.config.hunger_rate,
+            self.config.hunger_shrink,
+            hunger_dt,
+        ) && self.snake.length() <= consts::SNAKE_MIN_LEN
+        {
+            return SimResult::GameOver(DeathCause::Hunger);
+        }
+        self.power_ups.tick(dt);
+        let is_ghost = self.power_ups.is_ghost();
+        self.spatial_hash.rebuild(&self.snake.get_all_bboxes());
+        let crash = if is_ghost || self.power_ups.is_in_shield_grace() {
+            None
+        } else {
+            crash_cause(
+                &self.snake,
+                Some(&self.spatial_hash),
+                self.snake.wall_collide(self.screen, 0.),
+                false,
+                &self.obstacles,
+                None,
+            )
+        };
+
+        for food in &mut self.foods {
+            if frozen {
+                food.tick_frozen(dt);
+            } else {
+                food.tick(dt);
+            }
+        }
+        self.foods.retain(|food| !food.is_expired());
+        self.refill_food();
+        let head = self.snake.head_position();
+        if let Some(idx) = magnet_target(&self.foods, head, &self.power_ups) {
+            self.foods[idx].move_towards(head, consts::MAGNET_SPEED * dt);
+        }
+
+        if let Some(idx) = eaten_food(&self.foods, &self.snake) {
+            let food = self.foods.swap_remove(idx);
+            self.hunger_timer = self.config.hunger_rate;
+            grow_from_food(&mut self.snake, food.kind);
+            self.combo += 1;
+            self.combo_timer = consts::COMBO_SECS;
+            let points = self
+                .power_ups
+                .multiply_score(food_points(&food, self.combo));
+            self.score += points;
+            self.power_ups.apply(food.kind);
+            self.refill_food();
+            if food.kind == FoodKind::Magnet {
+                if let Some(dir) = magnet_impulse(&mut self.snake, &self.foods, &self.power_ups) {
+                    self.inputs.clear();
+                    self.inputs.push_front(dir);
+                }
+            }
+            SimResult::Ate(points)
+        } else if crash.is_some() && block_crash(&mut self.snake, &mut self.power_ups) {
+            SimResult::Running
+        } else if let Some(cause) = crash {
+            SimResult::GameOver(cause)
+        } else {
+            let mut speed = self.speed;
+            if self.power_ups.is_speed_boosted() {
+                speed *= consts::SPEED_BOOST_MULT;
+            }
+            self.snake.do_move(dt * speed);
+            if is_ghost {
+                self.snake.wrap_around(self.screen);
+            }
+            SimResult::Running
+        }
+    }
+
+    fn refill_food(&mut self) {
+        while self.foods.len() < self.max_food {
+            let (obstacles, foods) = (&self.obstacles, &self.foods);
+            let food = Food::spawn_away_from(
+                &mut self.rng,
+                self.screen,
+                0.,
+                self.config.food_max_age,
+                &self.snake,
+                consts::FOOD_MIN_HEAD_DISTANCE,
+                |food| {
+                    obstacles
+                        .iter()
+                        .any(|obstacle| obstacle.bbox.overlaps(&food.bbox))
+                        || foods.iter().any(|other| other.bbox.overlaps(&food.bbox))
+                },
+            );
+            match food {
+                Some(food) => self.foods.push(food),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Returns the middle of a random free cell of `size`, out of the cells that fill `bounds`
+/// with `margin` left out along the edges. Cells are checked in the order shuffled with `rng`
+/// and `is_free` tells whether the area of a cell can be used.
+///
+/// Cells at least `min_distance` away from `head` are preferred,
+/// the ones nearer are used only when nothing else is free.
+/// Returns `None` when no cell is free.
+///
+pub fn find_free_cell(
+    rng: &mut impl Rng,
+    bounds: Coords,
+    margin: f32,
+    size: Coords,
+    head: Coords,
+    min_distance: f32,
+    is_free: impl Fn(Rect) -> bool,
+) -> Option<Coords> {
+    let cols = ((bounds.x - 2. * margin) / size.x).floor().max(0.) as usize;
+    let rows = ((bounds.y - 2. * margin) / size.y).floor().max(0.) as usize;
+    let mut cells = (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (col, row)))
+        .collect_vec();
+    cells.shuffle(rng);
+
+    let mut near_head = None;
+    for (col, row) in cells {
+        let area = Rect::new(
+            margin + col as f32 * size.x,
+            margin + row as f32 * size.y,
+            size.x,
+            size.y,
+        );
+        if !is_free(area) {
+            continue;
+        }
+        let center = Coords::new(area.x + area.w / 2., area.y + area.h / 2.);
+        if center.distance(&head) >= min_distance {
+            return Some(center);
+        }
+        near_head.get_or_insert(center);
+    }
+    near_head
+}
+
+/// Turns the snake towards the oldest queued direction that is not colinear
+/// with the current one, dropping it and every older input from the queue.
+///
+/// Returns the new direction, when the snake turned.
+///
+pub fn apply_queued_turn(inputs: &mut VecDeque<Direction>, snake: &mut Snake) -> Option<Direction> {
+    if let Some((idx, &new_dir)) =
+        it::rev(inputs.iter()).find_position(|dir| !dir.is_colinear(snake.dir))
+    {
+        let truncated_len = inputs.len() - idx - 1;
+        inputs.truncate(truncated_len);
+        snake.dir = new_dir;
+        Some(new_dir)
+    } else {
+        inputs.clear();
+        None
+    }
+}
+
+/// Returns why `snake` dies in its current position, if it does.
+/// `hash`, when given, has to hold the current segments of `snake`.
+/// `hits_wall` and `on_void` tell if the head is out of the safe area or above the void,
+/// `other` is the snake of the other player.
+///
+pub fn crash_cause(
+    snake: &Snake,
+    hash: Option<&SpatialHash>,
+    hits_wall: bool,
+    on_void: bool,
+    obstacles: &[Obstacle],
+    other: Option<&Snake>,
+) -> Option<DeathCause> {
+    let bitten = match hash {
+        Some(hash) => snake.self_collide_near(hash),
+        None => snake.self_collide(),
+    };
+    if bitten.is_some() {
+        Some(DeathCause::SelfCollision)
+    } else if hits_wall {
+        Some(DeathCause::Wall)
+    } else if on_void {
+        Some(DeathCause::Void)
+    } else if let Some(obstacle) = snake.obstacle_collide(obstacles) {
+        Some(DeathCause::Obstacle(obstacle.name.clone()))
+    } else if other.is_some_and(|other| snake.collide_snake(other)) {
+        Some(DeathCause::OtherSnake)
+    } else {
+        None
+    }
+}
+
+/// Counts the hunger of `snake` down by `dt`. Once it runs out, the snake
+/// is shortened by `shrink` and the countdown starts again from `rate`.
+///
+/// Returns whether the snake has just been shortened.
+///
+pub fn starve(snake: &mut Snake, hunger_timer: &mut f32, rate: f32, shrink: f32, dt: f32) -> bool {
+    *hunger_timer = f32::max(*hunger_timer - dt, 0.);
+    if *hunger_timer > 0. {
+        return false;
+    }
+
+    *hunger_timer = rate;
+    snake.trim_to_length(snake.length() - shrink);
+    true
+}
+
+/// Returns the index of the first eatable food that `snake` touches
+///
+pub fn eaten_food(foods: &[Food], snake: &Snake) -> Option<usize> {
+    foods
+        .iter()
+        .position(|food| food.is_materialized() && snake.collide(&food.bbox))
+}
+
+/// Returns the index of the food nearest to `pos`, out of the ones accepted by `filter`
+///
+pub fn nearest_food(foods: &[Food], pos: Coords, filter: impl Fn(&Food) -> bool) -> Option<usize> {
+    foods
+        .iter()
+        .enumerate()
+        .filter(|(_, food)| filter(food))
+        .min_by(|(_, a), (_, b)| {
+            a.center()
+                .distance(&pos)
+                .total_cmp(&b.center().distance(&pos))
+        })
+        .map(|(idx, _)| idx)
+}
+
+/// Returns the index of the food pulled by the magnet: the eatable one nearest to `head`,
+/// or `None` when the magnet is not active
+///
+pub fn magnet_target(foods: &[Food], head: Coords, power_ups: &PowerUpState) -> Option<usize> {
+    if !power_ups.is_magnetic() {
+        return None;
+    }
+    nearest_food(foods, head, Food::is_materialized)
+}
+
+/// Shoves `snake` by `consts::MAGNET_IMPULSE` towards the food the magnet pulls,
+/// along the direction that leads closest to it, but never back into the body.
+///
+/// Returns the direction the snake was shoved in, which the caller has to queue as its input.
+///
+pub fn magnet_impulse(
+    snake: &mut Snake,
+    foods: &[Food],
+    power_ups: &PowerUpState,
+) -> Option<Direction> {
+    let head = snake.head_position();
+    let idx = magnet_target(foods, head, power_ups)?;
+    let delta = foods[idx].center() - head;
+    let back = snake.dir.opposite();
+    let dir = Direction::all()
+        .iter()
+        .copied()
+        .filter(|&dir| dir != back)
+        .max_by(|a, b| {
+            delta
+                .dot(&a.as_coords())
+                .total_cmp(&delta.dot(&b.as_coords()))
+        })?;
+
+    if snake.apply_impulse(dir, consts::MAGNET_IMPULSE) {
+        Some(dir)
+    } else {
+        None
+    }
+}
+
+/// Makes `snake` longer after eating food of the given kind,
+/// or shorter for `FoodKind::Shrink`
+///
+pub fn grow_from_food(snake: &mut Snake, kind: FoodKind) {
+    if kind == FoodKind::Shrink {
+        snake.cut_tail(consts::SHRINK_LEN);
+    } else {
+        snake.grow(consts::FOOD_SIZE);
+    }
+}
+
+/// Returns the multiplier of the combo with `combo` food eaten in a row
+///
+pub fn combo_multiplier(combo: u32) -> u32 {
+    1 + combo / 2
+}
+
+/// Returns the points for eating `food`, where `combo` already counts it,
+/// before power-up multipliers. Fresher food is worth more.
+///
+pub fn food_points(food: &Food, combo: u32) -> u32 {
+    let time_bonus = (food.lifetime_left() * consts::FOOD_TIME_BONUS) as u32;
+    (1 + time_bonus) * combo_multiplier(combo)
+}
+
+/// Uses up the shield to survive a crash, pulling the head of `snake` back to where it was safe.
+/// Crashes are then ignored for `consts::SHIELD_GRACE_SECS`, see `PowerUpState::take_shield`.
+/// Returns whether the crash was blocked.
+///
+pub fn block_crash(snake: &mut Snake, power_ups: &mut PowerUpState) -> bool {
+    if !power_ups.take_shield() {
+        return false;
+    }
+    snake.retract(consts::SNAKE_WIDTH);
+    true
+}
+
+/// Outcome of `run_benchmark`, printed as `key=value` pairs
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct BenchmarkResult {
+    pub games_per_second: f64,
+    pub mean_score: f64,
+    pub p99_score: u32,
+}
+
+impl fmt::Display for BenchmarkResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            BENCHMARK_FMT!(),
+            self.games_per_second, self.mean_score, self.p99_score
+        )
+    }
+}
+
+/// Play a single run seeded with `seed`, steered by `ai`, and return its score.
+/// A run that lasts longer than `consts::HEADLESS_MAX_SECS` is stopped, as the AI may loop forever.
+///
+pub fn play_ai_game(config: &GameConfig, seed: u64, ai: &mut AiController) -> u32 {
+    let mut sim = Simulation::new(config, seed);
+    while sim.run_time < consts::HEADLESS_MAX_SECS {
+        let head = sim.snake.head_position();
+        let nearest = nearest_food(&sim.foods, head, |_| true);
+        let blocked: Vec<_> = sim.obstacles.iter().map(|obstacle| obstacle.bbox).collect();
+        let input =
+            nearest.map(|idx| ai.next_direction(&sim.snake, &sim.foods[idx], &blocked, sim.screen));
+        if let SimResult::GameOver(_) = sim.tick(input) {
+            break;
+        }
+    }
+
+    sim.score
+}
+
+/// Play `games` runs steered by `AiController` and print their summary to stdout.
+/// Runs are seeded one after another from `seed`, or randomly without it.
+///
+pub fn run_headless(config: &GameConfig, seed: Option<u64>, games: u32) {
+    let start = Instant::now();
+    let mut ai = AiController::default();
+    let scores: Vec<u32> = (0..games)
+        .map(|game| {
+            let seed = seed.map_or_else(rand::random, |seed| seed.wrapping_add(game as u64));
+            play_ai_game(config, seed, &mut ai)
+        })
+        .collect();
+    let secs = start.elapsed().as_secs_f64();
+
+    let average = scores.iter().sum::<u32>() as f64 / f64::from(games.max(1));
+    let max = scores.iter().copied().max().unwrap_or(0);
+    let games_per_sec = if secs > 0. { games as f64 / secs } else { 0. };
+    println!(HEADLESS_SUMMARY_FMT!(), games, average, max, games_per_sec);
+}
+
+/// Play `num_games` runs steered by `AiController`, seeded with 0, 1, 2 and so on,
+/// so every benchmark plays the same games, and measure how long they took.
+///
+pub fn run_benchmark(config: &GameConfig, num_games: u32) -> BenchmarkResult {
+    let start = Instant::now();
+    let mut ai = AiController::default();
+    let mut scores: Vec<u32> = (0..num_games)
+        .map(|game| play_ai_game(config, game as u64, &mut ai))
+        .collect();
+    let secs = start.elapsed().as_secs_f64();
+
+    scores.sort_unstable();
+    let p99_idx = ((scores.len() as f64 * 0.99).ceil() as usize).saturating_sub(1);
+    BenchmarkResult {
+        games_per_second: if secs > 0. {
+            num_games as f64 / secs
+        } else {
+            0.
+        },
+        mean_score: scores.iter().sum::<u32>() as f64 / f64::from(num_games.max(1)),
+        p99_score: scores.get(p99_idx).copied().unwrap_or(0),
+    }
+}
@@ -1,8 +1,9 @@
 use crate::game::{consts, coords::Coords, direction::Direction, line::Line, turn::Turn};
+use crate::game::{obstacle::Obstacle, savestate::SegmentSnapshot, spatial::SpatialHash};
 use ggez::graphics::Rect;
 use std::collections::VecDeque;
 
-use super::segment::Segment;
+use super::segment::{Growable, Segment};
 
 /// Snake structure that consists of a list of either
 /// straight or curved segments and the direction of the head.
@@ -17,10 +18,14 @@ impl Snake {
     /// on the `(x, y)` position, pointing down.
     ///
     pub fn new(x: f32, y: f32) -> Self {
+        let beg = Coords::new(x, y - consts::SNAKE_START_HEIGHT / 2.);
+        let end = Coords::new(x, y + consts::SNAKE_START_HEIGHT / 2.);
         let first = Line {
-            beg: Coords::new(x, y - consts::SNAKE_START_HEIGHT / 2.),
-            end: Coords::new(x, y + consts::SNAKE_START_HEIGHT / 2.),
+            beg,
+            end,
             dir: Direction::Down,
+            prev_beg: beg,
+            prev_end: end,
         };
         let boxed: Box<dyn Segment> = Box::new(first);
         let mut body = VecDeque::new();
@@ -33,18 +38,66 @@ impl Snake {
     }
 
     /// Move `Snake` in the current direction by a given distance.
+    /// Positions from before the move are kept for the interpolated drawing.
     ///
     pub fn do_move(&mut self, dist: f32) {
+        for segment in &mut self.body {
+            segment.store_previous();
+        }
         self.grow(dist);
         self.shrink(dist);
     }
 
     fn shrink(&mut self, dist: f32) {
-        let back = self.body.back_mut().unwrap();
-        let shrink_left = back.shrink(dist);
-        if shrink_left > 0. {
+        let mut shrink_left = self.body.back_mut().unwrap().shrink(dist);
+        // segments shrunk to nothing go away as well
+        while self.body.len() > 1 && (shrink_left > 0. || self.body.back().unwrap().size() <= 0.) {
             self.body.pop_back();
-            self.body.back_mut().unwrap().shrink(shrink_left);
+            shrink_left = self.body.back_mut().unwrap().shrink(shrink_left);
+        }
+    }
+
+    /// Return position of the head, the growing end of the front segment.
+    ///
+    pub fn head_position(&self) -> Coords {
+        self.body.front().unwrap().end()
+    }
+
+    /// Return position of the tail, the shrinking end of the rearmost segment.
+    ///
+    pub fn tail_position(&self) -> Coords {
+        self.body.back().unwrap().beg()
+    }
+
+    /// Return the direction the tail moves in, the one of the rearmost segment.
+    ///
+    pub fn tail_direction(&self) -> Direction {
+        self.body
+            .back()
+            .map_or(self.dir, |segment| segment.direction())
+    }
+
+    /// Return total length of all segments, in pixels.
+    ///
+    pub fn length(&self) -> f32 {
+        self.body.iter().map(|segment| segment.size()).sum()
+    }
+
+    /// Shorten `Snake` from the tail by a given distance,
+    /// but never below `consts::SNAKE_MIN_LEN`.
+    ///
+    pub fn cut_tail(&mut self, dist: f32) {
+        self.trim_to_length(self.length() - dist);
+    }
+
+    /// Shorten `Snake` from the tail until it is at most `max_len` long,
+    /// but never below `consts::SNAKE_MIN_LEN`, so it does not disappear.
+    /// Segments that shrink to nothing are removed.
+    ///
+    pub fn trim_to_length(&mut self, max_len: f32) {
+        let excess = self.length() - f32::max(max_len, consts::SNAKE_MIN_LEN);
+        if excess > 0. {
+            self.shrink(excess);
         }
     }
 
@@ -67,8 +120,89 @@ impl Snake {
         if growth_left > 0. {
             let pos = front.end();
             let dir = front.direction();
-            self.body.push_front(Box::new(Line::new(pos, dir)));
-            self.body.front_mut().unwrap().grow(growth_left);
+            // the new line takes exactly what is left, instead of its initial length
+            let mut line = Line::new(pos, dir);
+            line.end = pos + dir.as_coords() * growth_left;
+            self.body.push_front(Box::new(line));
+        }
+    }
+
+    /// Shove `Snake` by `dist` at once, bypassing the movement timer,
+    /// and keep it heading in `dir` afterwards. The tail follows, so the length stays the same.
+    ///
+    /// When `dir` differs from the current direction, the snake turns onto it first,
+    /// just like on a regular turn. Shoving it back into its own body is rejected.
+    ///
+    /// Returns whether the snake was shoved.
+    ///
+    pub fn apply_impulse(&mut self, dir: Direction, dist: f32) -> bool {
+        if dir == self.dir.opposite() {
+            return false;
+        }
+        self.dir = dir;
+        self.do_move(dist);
+        true
+    }
+
+    /// Pull the head back along the body by `dist`, as if the last moves were undone,
+    /// and keep the snake heading where its new front segment points.
+    /// The tail is pushed back by as much as the head was pulled, so the length stays the same.
+    ///
+    /// Front segments shorter than what is left to pull back are removed,
+    /// except the last one, so the snake does not disappear.
+    /// A turn reached on the way is removed as a whole, so the head ends up
+    /// on the straight segment from before the turn.
+    ///
+    pub fn retract(&mut self, dist: f32) {
+        let length = self.length();
+        let mut left = dist;
+        while self.body.len() > 1 {
+            let front = self.body.front().unwrap();
+            let is_turn = matches!(front.snapshot(), SegmentSnapshot::Turn(_));
+            if !is_turn && front.size() > left {
+                break;
+            }
+            left = f32::max(left - front.size(), 0.);
+            self.body.pop_front();
+        }
+        if let SegmentSnapshot::Line(mut line) = self.body.front().unwrap().snapshot() {
+            let pulled = f32::min(left, line.size() - consts::POINT_EPSILON);
+            line.end -= line.dir.as_coords() * f32::max(pulled, 0.);
+            line.store_previous();
+            self.body[0] = Box::new(line);
+        }
+        self.dir = self.body.front().unwrap().direction();
+        self.extend_tail(length - self.length());
+    }
+
+    /// Lengthen `Snake` by `dist` on the tail side, back against the direction the tail moves in.
+    /// A turn at the tail is filled up first, a straight segment from before it takes the rest.
+    ///
+    fn extend_tail(&mut self, dist: f32) {
+        if dist <= 0. {
+            return;
+        }
+        match self.body.back().unwrap().snapshot() {
+            SegmentSnapshot::Line(mut line) => {
+                line.beg -= line.dir.as_coords() * dist;
+                line.store_previous();
+                *self.body.back_mut().unwrap() = Box::new(line);
+            }
+            SegmentSnapshot::Turn(mut turn) => {
+                let filled = f32::min(dist, (1. - turn.percentage) * consts::SNAKE_WIDTH);
+                turn.percentage += filled / consts::SNAKE_WIDTH;
+                turn.store_previous();
+                *self.body.back_mut().unwrap() = Box::new(turn);
+
+                let rest = dist - filled;
+                if rest > 0. {
+                    let mut line =
+                        Line::new(turn.pos - turn.in_dir.as_coords() * rest, turn.in_dir);
+                    line.end = turn.pos;
+                    line.store_previous();
+                    self.body.push_back(Box::new(line));
+                }
+            }
         }
     }
 
@@ -78,24 +212,399 @@ impl Snake {
         self.body.iter().any(|segment| segment.collision(other))
     }
 
-    /// Check if head is colliding with screen boundaries.
+    /// Return the index of the first segment, counting from the head,
+    /// whose bounding box contains `pos`.
+    ///
+    pub fn segment_at_position(&self, pos: Coords) -> Option<usize> {
+        self.body
+            .iter()
+            .position(|segment| segment.bounding_box().contains(pos))
+    }
+
+    /// Return the layer the snake is drawn in, the one of its head
+    ///
+    pub fn z_order(&self) -> i32 {
+        self.body.front().unwrap().z_order()
+    }
+
+    /// Return bounding boxes of all segments, from the head to the tail
     ///
-    pub fn wall_collide(&self) -> bool {
+    pub fn get_all_bboxes(&self) -> Vec<Rect> {
+        self.body
+            .iter()
+            .map(|segment| segment.bounding_box())
+            .collect()
+    }
+
+    /// Return the middle of every segment, from the head to the tail.
+    /// Straight segments give their midpoint, turns the middle of their bounding box.
+    ///
+    pub fn segment_centers(&self) -> Vec<Coords> {
+        self.body
+            .iter()
+            .map(|segment| match segment.snapshot() {
+                SegmentSnapshot::Line(line) => line.midpoint(),
+                SegmentSnapshot::Turn(_) => {
+                    let bbox = segment.bounding_box();
+                    Coords::new(bbox.x + bbox.w / 2., bbox.y + bbox.h / 2.)
+                }
+            })
+            .collect()
+    }
+
+    /// Check if head is colliding with screen boundaries,
+    /// or is less than `margin` away from any of them.
+    ///
+    pub fn wall_collide(&self, bounds: Coords, margin: f32) -> bool {
         let head = self.body.front().unwrap().bounding_box();
-        head.left() < -consts::WALL_MARGIN
-            || head.top() < -consts::WALL_MARGIN
-            || head.bottom() > consts::SCREEN_SIZE.y + consts::WALL_MARGIN
-            || head.right() > consts::SCREEN_SIZE.x + consts::WALL_MARGIN
+        let limit = margin - consts::WALL_MARGIN;
+        head.left() < limit
+            || head.top() < limit
+            || head.bottom() > bounds.y - limit
+            || head.right() > bounds.x - limit
     }
 
-    /// Check if head is colliding with any other segment.
+    /// Move head to the opposite side of the screen, when it went past one of the edges.
+    /// A new `Line` is started there, so all the segments keep valid bounding boxes.
+    ///
+    /// Returns `true` when the head was wrapped.
+    ///
+    pub fn wrap_around(&mut self, bounds: Coords) -> bool {
+        let front = self.body.front().unwrap();
+        let pos = front.end();
+        let wrapped = Coords::new(pos.x.rem_euclid(bounds.x), pos.y.rem_euclid(bounds.y));
+        if wrapped == pos {
+            return false;
+        }
+
+        let dir = front.direction();
+        self.body.push_front(Box::new(Line::new(wrapped, dir)));
+        true
+    }
+
+    /// Move the whole `Snake`, so all its segments fit inside the screen of the given size.
+    /// When the snake is bigger than the screen, it is aligned to the top left corner.
+    ///
+    pub fn clamp_to(&mut self, bounds: Coords) {
+        let boxes = self.get_all_bboxes();
+        let area = boxes[1..]
+            .iter()
+            .fold(boxes[0], |area, &bbox| area.combine_with(bbox));
+
+        let clamp_offset = |min: f32, max: f32, bound: f32| {
+            if min < 0. {
+                -min
+            } else if max > bound {
+                f32::max(bound - max, -min)
+            } else {
+                0.
+            }
+        };
+        let offset = Coords::new(
+            clamp_offset(area.left(), area.right(), bounds.x),
+            clamp_offset(area.top(), area.bottom(), bounds.y),
+        );
+        if offset != Coords::default() {
+            for segment in &mut self.body {
+                segment.shift(offset);
+            }
+        }
+    }
+
+    /// Move head to `pos`, where it continues towards `dir`.
+    /// Just like in `wrap_around`, a new `Line` is started there.
+    ///
+    pub fn teleport(&mut self, pos: Coords, dir: Direction) {
+        self.dir = dir;
+        self.body.push_front(Box::new(Line::new(pos, dir)));
+    }
+
+    /// Check if head is colliding with given `Rect`.
     ///
-    pub fn self_collide(&self) -> bool {
+    pub fn head_collide(&self, other: &Rect) -> bool {
+        self.body.front().unwrap().collision(other)
+    }
+
+    /// Check if head is colliding with any of the obstacles.
+    ///
+    /// Returns the obstacle that was hit.
+    ///
+    pub fn obstacle_collide<'a>(&self, obstacles: &'a [Obstacle]) -> Option<&'a Obstacle> {
+        obstacles
+            .iter()
+            .find(|obstacle| self.head_collide(&obstacle.bbox))
+    }
+
+    /// Check if head is colliding with any segment of the `other` snake.
+    ///
+    pub fn collide_snake(&self, other: &Snake) -> bool {
         let head = self.body.front().unwrap();
+        other
+            .get_all_bboxes()
+            .iter()
+            .any(|bbox| head.collision(bbox))
+    }
+
+    /// Check if head is colliding with any other segment.
+    ///
+    /// Returns the bounding box of the segment that was hit.
+    ///
+    pub fn self_collide(&self) -> Option<Rect> {
         self.body
             .iter()
             .skip(1)
-            .map(|x| x.bounding_box())
-            .any(|x| head.collision(&x))
+            .find(|segment| self.head_hits(segment.as_ref()))
+            .map(|segment| segment.bounding_box())
+    }
+
+    /// Same as `self_collide`, but checks only the segments that `hash` has near the head.
+    /// `hash` has to be rebuilt from `get_all_bboxes` since the last change of the snake.
+    ///
+    pub fn self_collide_near(&self, hash: &SpatialHash) -> Option<Rect> {
+        let head = self.body.front().unwrap();
+        hash.query(head.bounding_box())
+            .into_iter()
+            .filter(|&idx| idx > 0)
+            .filter_map(|idx| self.body.get(idx))
+            .find(|segment| self.head_hits(segment.as_ref()))
+            .map(|segment| segment.bounding_box())
+    }
+
+    /// Check if the head runs into `segment`. Their bounding boxes have to overlap and,
+    /// for a straight segment, the tip of the head has to lie on it as well,
+    /// so a head only brushing past its side does not count.
+    ///
+    fn head_hits(&self, segment: &dyn Segment) -> bool {
+        let head = self.body.front().unwrap();
+        head.collision(&segment.bounding_box())
+            && match segment.snapshot() {
+                SegmentSnapshot::Line(line) => line.contains_point(head.end()),
+                SegmentSnapshot::Turn(_) => true,
+            }
+    }
+
+    /// Return the middle of the area where the head overlaps `other`.
+    ///
+    pub fn contact_point(&self, other: &Rect) -> Coords {
+        let head = self.body.front().unwrap().bounding_box();
+        let left = f32::max(head.left(), other.left());
+        let right = f32::min(head.right(), other.right());
+        let top = f32::max(head.top(), other.top());
+        let bottom = f32::min(head.bottom(), other.bottom());
+        Coords::new((left + right) / 2., (top + bottom) / 2.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < EPSILON,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn new_snake_has_start_length() {
+        let snake = Snake::new(100., 100.);
+        assert_close(snake.length(), consts::SNAKE_START_HEIGHT);
+    }
+
+    #[test]
+    fn new_snake_tail_moves_in_start_direction() {
+        let snake = Snake::new(100., 100.);
+        assert_eq!(snake.tail_direction(), snake.dir);
+
+        let snake = turned_snake();
+        assert_eq!(snake.tail_direction(), Direction::Down);
+        assert_eq!(snake.dir, Direction::Right);
+    }
+
+    #[test]
+    fn grow_straight_adds_exact_length() {
+        let mut snake = Snake::new(100., 100.);
+        let before = snake.length();
+        snake.grow(7.5);
+        assert_close(snake.length(), before + 7.5);
+        assert_eq!(snake.body.len(), 1);
+    }
+
+    #[test]
+    fn grow_into_turn_adds_exact_length() {
+        let mut snake = Snake::new(100., 100.);
+        let before = snake.length();
+        snake.dir = Direction::Right;
+        snake.grow(consts::SNAKE_WIDTH / 2.);
+        assert_close(snake.length(), before + consts::SNAKE_WIDTH / 2.);
+        assert_eq!(snake.body.len(), 2);
+    }
+
+    #[test]
+    fn grow_past_turn_adds_exact_length() {
+        let mut snake = Snake::new(100., 100.);
+        let before = snake.length();
+        snake.dir = Direction::Right;
+        snake.grow(consts::SNAKE_WIDTH * 1.5);
+        assert_close(snake.length(), before + consts::SNAKE_WIDTH * 1.5);
+        assert_eq!(snake.body.len(), 3);
+    }
+
+    #[test]
+    fn head_and_tail_are_the_snake_ends() {
+        let snake = Snake::new(100., 100.);
+        let half = consts::SNAKE_START_HEIGHT / 2.;
+        assert_eq!(snake.head_position(), Coords::new(100., 100. + half));
+        assert_eq!(snake.tail_position(), Coords::new(100., 100. - half));
+    }
+
+    #[test]
+    fn do_move_shifts_head_and_tail_in_direction() {
+        let mut snake = Snake::new(100., 100.);
+        let (head, tail) = (snake.head_position(), snake.tail_position());
+        snake.do_move(5.);
+        assert_eq!(
+            snake.head_position(),
+            head + Direction::Down.as_coords() * 5.
+        );
+        assert_eq!(
+            snake.tail_position(),
+            tail + Direction::Down.as_coords() * 5.
+        );
+        assert_close(snake.length(), consts::SNAKE_START_HEIGHT);
+    }
+
+    /// Snake made of a line going right, a turn and a line going down, 160 pixels long
+    fn turned_snake() -> Snake {
+        let mut snake = Snake::new(100., 100.);
+        snake.dir = Direction::Right;
+        snake.do_move(consts::SNAKE_WIDTH * 4.);
+        snake
+    }
+
+    #[test]
+    fn trim_to_length_across_segments() {
+        let mut snake = turned_snake();
+        assert_eq!(snake.body.len(), 3);
+        // takes the whole line going down and half of the turn
+        let max_len = snake.length() - snake.body.back().unwrap().size() - consts::SNAKE_HALF_WIDTH;
+        snake.trim_to_length(max_len);
+        assert_close(snake.length(), max_len);
+        assert_eq!(snake.body.len(), 2);
+    }
+
+    #[test]
+    fn trim_to_length_stops_at_minimum() {
+        let mut snake = turned_snake();
+        snake.trim_to_length(0.);
+        assert_close(snake.length(), consts::SNAKE_MIN_LEN);
+        snake.cut_tail(consts::SNAKE_WIDTH);
+        assert_close(snake.length(), consts::SNAKE_MIN_LEN);
+    }
+
+    #[test]
+    fn segment_trimmed_to_zero_is_removed() {
+        let mut snake = turned_snake();
+        let tail_len = snake.body.back().unwrap().size();
+        snake.cut_tail(tail_len);
+        assert_eq!(snake.body.len(), 2);
+        assert_close(snake.length(), consts::SNAKE_START_HEIGHT - tail_len);
+    }
+
+    #[test]
+    fn self_collide_returns_a_segment_of_the_body() {
+        let mut snake = Snake::new(100., 100.);
+        snake.grow(consts::SNAKE_WIDTH * 20.);
+        assert_eq!(snake.self_collide(), None);
+
+        // a small loop, ending with the head pushed into the first part of the body
+        for &(dir, steps) in &[
+            (Direction::Right, 15),
+            (Direction::Up, 15),
+            (Direction::Left, 17),
+        ] {
+            snake.dir = dir;
+            for _ in 0..steps {
+                snake.do_move(consts::SNAKE_WIDTH / 5.);
+            }
+        }
+        let hit = snake.self_collide().expect("snake should bite itself");
+        let head = snake.body.front().unwrap();
+        assert!(snake
+            .body
+            .iter()
+            .skip(1)
+            .any(|segment| segment.bounding_box() == hit));
+        assert!(head.collision(&hit));
+    }
+
+    #[test]
+    fn impulse_keeps_length_and_rejects_going_back() {
+        let mut snake = Snake::new(100., 100.);
+        let head = snake.head_position();
+        assert!(!snake.apply_impulse(Direction::Up, consts::SNAKE_WIDTH));
+        assert_eq!(snake.head_position(), head);
+
+        assert!(snake.apply_impulse(Direction::Down, consts::SNAKE_WIDTH));
+        assert_eq!(
+            snake.head_position(),
+            head + Direction::Down.as_coords() * consts::SNAKE_WIDTH
+        );
+        assert!(snake.apply_impulse(Direction::Left, consts::SNAKE_WIDTH));
+        assert_eq!(snake.dir, Direction::Left);
+        assert_close(snake.length(), consts::SNAKE_START_HEIGHT);
+    }
+
+    #[test]
+    fn retract_pulls_the_head_back_and_keeps_length() {
+        let mut snake = Snake::new(100., 100.);
+        let (head, tail) = (snake.head_position(), snake.tail_position());
+        snake.retract(consts::SNAKE_WIDTH);
+
+        let back = Direction::Up.as_coords() * consts::SNAKE_WIDTH;
+        assert_eq!(snake.head_position(), head + back);
+        assert_eq!(snake.tail_position(), tail + back);
+        assert_eq!(snake.dir, Direction::Down);
+        assert_close(snake.length(), consts::SNAKE_START_HEIGHT);
+    }
+
+    #[test]
+    fn retract_right_after_a_turn_goes_back_before_the_turn() {
+        let mut snake = Snake::new(100., 100.);
+        snake.grow(consts::SNAKE_WIDTH * 5.);
+        let corner = snake.head_position();
+        // the turn is fully grown and the head is just past it, when it crashes
+        snake.dir = Direction::Right;
+        snake.do_move(consts::SNAKE_WIDTH + consts::SNAKE_HALF_WIDTH);
+        assert_eq!(snake.body.len(), 3);
+        let length = snake.length();
+
+        snake.retract(consts::SNAKE_WIDTH);
+        assert!(matches!(
+            snake.body.front().unwrap().snapshot(),
+            SegmentSnapshot::Line(_)
+        ));
+        assert_eq!(snake.dir, Direction::Down);
+        assert_close(snake.head_position().distance(&corner), 0.);
+        assert_close(snake.length(), length);
+    }
+
+    #[test]
+    fn segment_at_head_position_is_the_head() {
+        let snake = turned_snake();
+        assert_eq!(snake.segment_at_position(snake.head_position()), Some(0));
+        assert_eq!(snake.segment_at_position(snake.tail_position()), Some(2));
+    }
+
+    #[test]
+    fn segment_at_position_off_snake_is_none() {
+        let snake = turned_snake();
+        assert_eq!(snake.segment_at_position(Coords::new(500., 500.)), None);
+        assert_eq!(snake.segment_at_position(Coords::new(-10., -10.)), None);
     }
 }
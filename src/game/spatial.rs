@@ -0,0 +1,67 @@
+//! Module with SpatialHash struct, for finding the bounding boxes near some area
+use ggez::graphics::Rect;
+use std::collections::HashMap;
+
+use crate::game::{consts, coords::Coords};
+
+/// Structure that sorts bounding boxes into a grid of square cells.
+///
+/// Each cell keeps indices of the boxes overlapping it,
+/// so a query has to check only the boxes from the cells around the queried area.
+///
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    pub cells: HashMap<(i32, i32), Vec<usize>>,
+    pub cell_size: f32,
+}
+
+impl SpatialHash {
+    /// Create empty `SpatialHash` with cells of the given size
+    ///
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cells: HashMap::new(),
+            cell_size,
+        }
+    }
+
+    /// Replace the content with `bboxes`, each one stored under its index
+    ///
+    pub fn rebuild(&mut self, bboxes: &[Rect]) {
+        self.cells.clear();
+        for (idx, &bbox) in bboxes.iter().enumerate() {
+            for cell in self.covered_cells(bbox) {
+                self.cells.entry(cell).or_default().push(idx);
+            }
+        }
+    }
+
+    /// Return indices of the boxes stored in the cells that `area` overlaps, in ascending order.
+    /// Boxes themselves may not overlap `area`, they only lie close to it.
+    ///
+    pub fn query(&self, area: Rect) -> Vec<usize> {
+        let mut found: Vec<usize> = self
+            .covered_cells(area)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    fn covered_cells(&self, area: Rect) -> impl Iterator<Item = (i32, i32)> {
+        let (first_col, first_row) = Coords::new(area.left(), area.top()).grid_cell(self.cell_size);
+        let (last_col, last_row) =
+            Coords::new(area.right(), area.bottom()).grid_cell(self.cell_size);
+        (first_row..=last_row)
+            .flat_map(move |row| (first_col..=last_col).map(move |col| (col, row)))
+    }
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self::new(consts::SPATIAL_CELL_SIZE)
+    }
+}
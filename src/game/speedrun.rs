@@ -0,0 +1,152 @@
+//! Module with the timer and the records of the speed-run mode
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::game::consts;
+
+/// Stopwatch measuring a speed run with the system clock,
+/// so the time does not drift with the frame rate.
+/// Time spent in the pause menu is not counted.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct SpeedRunTimer {
+    start: Instant,
+    paused_at: Option<Instant>,
+    paused: Duration,
+}
+
+impl SpeedRunTimer {
+    /// Create a timer running from now
+    ///
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            paused_at: None,
+            paused: Duration::ZERO,
+        }
+    }
+
+    /// Stop counting time, until `resume` is called
+    ///
+    pub fn pause(&mut self) {
+        self.paused_at.get_or_insert_with(Instant::now);
+    }
+
+    /// Continue counting time after `pause`
+    ///
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += paused_at.elapsed();
+        }
+    }
+
+    /// Return the time counted so far, in milliseconds
+    ///
+    pub fn elapsed_ms(&self) -> u64 {
+        let end = self.paused_at.unwrap_or_else(Instant::now);
+        (end.duration_since(self.start) - self.paused).as_millis() as u64
+    }
+}
+
+/// Return the time as minutes, seconds and milliseconds, like 01:05.042
+///
+pub fn format_time(time_ms: u64) -> String {
+    format!(
+        SPEEDRUN_TIME_FMT!(),
+        time_ms / 60_000,
+        time_ms / 1000 % 60,
+        time_ms % 1000
+    )
+}
+
+/// Single finished speed run
+///
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct SpeedRunRecord {
+    pub target_score: u32,
+    pub time_ms: u64,
+}
+
+/// Structure with the best times of every target score played so far,
+/// at most `consts::SPEEDRUN_RECORDS` per target, from the fastest one.
+///
+/// Records are kept as a JSON file next to the high score file.
+///
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct SpeedRunRecords {
+    pub records: Vec<SpeedRunRecord>,
+}
+
+impl SpeedRunRecords {
+    /// Read records from the file. Missing or malformed file counts as no runs finished.
+    ///
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write records into the file, creating missing directories
+    ///
+    pub fn save(&self) {
+        if let Some(path) = Self::path() {
+            let content =
+                serde_json::to_string(self).expect("Error while serializing speed-run records");
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, content));
+            if let Err(err) = written {
+                eprintln!(
+                    "Could not save speed-run records to {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// Return the best times for the target score, from the fastest one
+    ///
+    pub fn top(&self, target_score: u32) -> Vec<u64> {
+        let mut times: Vec<u64> = self
+            .records
+            .iter()
+            .filter(|record| record.target_score == target_score)
+            .map(|record| record.time_ms)
+            .collect();
+        times.sort_unstable();
+        times
+    }
+
+    /// Add a finished run and drop the times of its target that no longer make the list.
+    /// Returns the place of the run on the list, counted from 0, if it made it.
+    ///
+    pub fn add(&mut self, record: SpeedRunRecord) -> Option<usize> {
+        let (mut same_target, others): (Vec<_>, Vec<_>) = self
+            .records
+            .drain(..)
+            .partition(|other| other.target_score == record.target_score);
+        same_target.sort_by_key(|other| other.time_ms);
+        // a run as fast as an older one goes after it
+        let place = same_target
+            .iter()
+            .position(|other| record.time_ms < other.time_ms)
+            .unwrap_or(same_target.len());
+        same_target.insert(place, record);
+        same_target.truncate(consts::SPEEDRUN_RECORDS);
+        self.records = others;
+        self.records.extend(same_target);
+        Some(place).filter(|&place| place < consts::SPEEDRUN_RECORDS)
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::SPEEDRUN_FILE))
+    }
+}
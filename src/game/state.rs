@@ -1,128 +1,2826 @@
-use std::{collections::VecDeque, time::Instant};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
 use ggez::{
-    graphics::{self, Font, Text, TextFragment},
-    Context,
+    audio::SoundSource,
+    conf::{self, FullscreenType},
+    event,
+    graphics::{self, Canvas, Font, Rect, Text, TextFragment},
+    Context, GameError, GameResult,
 };
-use itertools::{self as it, Itertools};
+use itertools::Itertools;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+use crate::game::achievements::AchievementTracker;
+use crate::game::ai::AiController;
+use crate::game::board::{self, Board, TileKind};
+use crate::game::config::{GameConfig, StartupArgs};
+use crate::game::daily::{self, DailyHighScore};
+use crate::game::danger::DangerLevel;
+use crate::game::death::DeathCause;
+use crate::game::debug::{self, FrameStats};
+use crate::game::difficulty::DifficultyLevel;
+use crate::game::event::{GameEvent, GameEventListener};
+use crate::game::food::{Food, FoodKind};
+use crate::game::i18n::{self, Strings};
+use crate::game::input::{self, GameAction, InputSource, KeyBindings};
+use crate::game::level::Level;
+use crate::game::menu::{MenuAction, MenuData, MenuItem};
+use crate::game::obstacle::Obstacle;
+use crate::game::palette::ColorPalette;
+use crate::game::particles::ParticleSystem;
+use crate::game::popup::ScorePopup;
+use crate::game::portal::Portal;
+use crate::game::powerup::PowerUpState;
+use crate::game::replay::{Replay, ReplayPlayer, ReplayRecorder};
+use crate::game::resourceloader::{self, MusicTrack, ResourceLoader};
+use crate::game::savestate::SaveState;
+use crate::game::segment::Renderable;
+use crate::game::settings::SettingsMenu;
+use crate::game::sim::{self, apply_queued_turn};
 use crate::game::snake::Snake;
-use crate::game::{consts, direction::Direction, food::Food, resourceloader::ResourceLoader};
+use crate::game::speedrun::{self, SpeedRunRecord, SpeedRunRecords, SpeedRunTimer};
+use crate::game::theme::ColorTheme;
+use crate::game::{consts, direction::Direction, maths};
+use crate::game::{coords::Coords, highscore::HighScoreManager, renderer::Renderer};
+use crate::game::{spatial::SpatialHash, stats::SessionStats};
 
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum GameState {
+    Menu,
+    Settings,
     PreGame,
     Game,
+    Paused,
+    /// Short break after losing a life, before the snake appears again
+    Respawning,
+    GameOver,
+}
+
+/// Enumeration of the rules a run is played with
+///
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Run lasts until the snake dies
+    Classic,
+    /// Run also ends once `duration` seconds have passed, eating food gives extra time
+    Timed { duration: f32 },
+    /// Snake speeds up and grows with time, the score is the time survived
+    Survival,
+    /// Snake is steered by `AiController` instead of the player
+    AutoPlay,
+    /// Deadly border around the screen grows with time
+    Shrinking,
+    /// Every round is played in a new randomly generated maze
+    Maze,
+    /// Classic rules with a seed of the current date, shared by all players
+    DailyChallenge,
+    /// Run ends once the score reaches `target_score`, as fast as possible
+    SpeedRun { target_score: u32 },
+}
+
+impl GameMode {
+    /// Returns the mode with the given name, as used on the command line.
+    /// Mode parameters are taken from `config`.
+    ///
+    pub fn from_name(name: &str, config: &GameConfig) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "classic" => Ok(GameMode::Classic),
+            "timed" => Ok(GameMode::Timed {
+                duration: config.timed_duration,
+            }),
+            "survival" => Ok(GameMode::Survival),
+            "autoplay" => Ok(GameMode::AutoPlay),
+            "shrinking" => Ok(GameMode::Shrinking),
+            "maze" => Ok(GameMode::Maze),
+            "daily" => Ok(GameMode::DailyChallenge),
+            "speedrun" => Ok(GameMode::SpeedRun {
+                target_score: config.speedrun_target,
+            }),
+            _ => Err(format!(
+                "Unknown game mode {}, expected classic, timed, survival, autoplay, shrinking, maze, daily or speedrun",
+                name
+            )),
+        }
+    }
+
+    /// Checks if a run may be saved and resumed later.
+    /// Daily challenges count only in one go, speed-run timers cannot be stored.
+    ///
+    pub fn can_save(&self) -> bool {
+        !matches!(self, GameMode::DailyChallenge | GameMode::SpeedRun { .. })
+    }
+
+    /// Returns how long a run may last, if it is limited
+    ///
+    pub fn time_limit(&self) -> Option<f32> {
+        match self {
+            GameMode::Classic
+            | GameMode::Survival
+            | GameMode::AutoPlay
+            | GameMode::Shrinking
+            | GameMode::Maze
+            | GameMode::DailyChallenge
+            | GameMode::SpeedRun { .. } => None,
+            GameMode::Timed { duration } => Some(*duration),
+        }
+    }
+}
+
+/// Element of the game world waiting to be drawn, with its `z_order`
+///
+type DrawLayer<'a> = (i32, Box<dyn Fn(&mut Context) + 'a>);
+
+/// Progress of a `GameMode::Survival` run
+///
+#[derive(Default, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SurvivalState {
+    pub elapsed: f32,
+    pub grow_timer: f32,
+}
+
+impl SurvivalState {
+    /// Returns how many times faster than normal the snake moves
+    ///
+    pub fn speed_multiplier(&self) -> f32 {
+        1. + self.elapsed / consts::SPEED_RAMP_PERIOD
+    }
+}
+
+/// Deadly border of a `GameMode::Shrinking` run.
+///
+/// Every `rate` seconds the border gets `consts::SNAKE_WIDTH` thicker on each side,
+/// until the safe zone is down to `consts::SHRINK_MIN_SAFE_ZONE`.
+///
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ShrinkBorder {
+    pub margin: f32,
+    pub rate: f32,
+    pub timer: f32,
+}
+
+impl Default for ShrinkBorder {
+    fn default() -> Self {
+        Self {
+            margin: 0.,
+            rate: consts::SHRINK_BORDER_SECS,
+            timer: 0.,
+        }
+    }
+}
+
+impl ShrinkBorder {
+    /// Advances the timer and thickens the border when it is time to.
+    /// Returns `true` when the border got thicker.
+    ///
+    pub fn tick(&mut self, dt: f32, screen: Coords) -> bool {
+        self.timer += dt;
+        if self.timer < self.rate {
+            return false;
+        }
+
+        self.timer -= self.rate;
+        let max_margin = f32::max(
+            (f32::min(screen.x, screen.y) - consts::SHRINK_MIN_SAFE_ZONE) / 2.,
+            0.,
+        );
+        let margin = f32::min(self.margin + consts::SNAKE_WIDTH, max_margin);
+        let grown = margin > self.margin;
+        self.margin = margin;
+        grown
+    }
+
+    /// Returns the four stripes along the screen edges that make up the border
+    ///
+    pub fn hazard_rects(&self, screen: Coords) -> Vec<Rect> {
+        if self.margin <= 0. {
+            return Vec::new();
+        }
+
+        let inner = screen.y - 2. * self.margin;
+        vec![
+            Rect::new(0., 0., screen.x, self.margin),
+            Rect::new(0., screen.y - self.margin, screen.x, self.margin),
+            Rect::new(0., self.margin, self.margin, inner),
+            Rect::new(screen.x - self.margin, self.margin, self.margin, inner),
+        ]
+    }
 }
 
 /// Structure for holding game data, managing player input
 /// and updating objects.
 ///
 pub struct GameData {
+    pub config: GameConfig,
+    pub screen: Coords,
     pub snake: Snake,
-    pub food: Food,
+    pub two_player: bool,
+    pub snake2: Option<Snake>,
+    pub obstacles: Vec<Obstacle>,
+    pub portals: Vec<Portal>,
+    pub board: Board,
+    pub ice_slide: u32,
+    pub ice_slide2: u32,
+    pub levels: Vec<Level>,
+    pub current_level: u32,
+    pub level_speed_mult: f32,
+    pub level_clear_timer: f32,
+    pub level_txt: Text,
+    pub level_clear_txt: Text,
+    pub inputs2: VecDeque<Direction>,
+    pub input_timer2: f32,
+    pub score2: u32,
+    pub score2_txt: Text,
+    pub two_player_txt: Text,
+    pub foods: Vec<Food>,
+    pub max_food: usize,
     pub delta_time: std::time::Instant,
+    pub accumulator: f32,
     pub inputs: VecDeque<Direction>,
+    pub input_source: InputSource,
+    pub key_bindings: KeyBindings,
     pub input_timer: f32,
     pub score: u32,
     pub score_txt: Text,
     pub pregame_txt: Text,
+    pub paused_txt: Text,
+    pub game_over_txt: Text,
+    pub last_death: Option<DeathCause>,
+    pub death_txt: Text,
+    pub final_score: u32,
+    pub final_score_txt: Text,
+    pub highscore: HighScoreManager,
+    pub highscore_txt: Text,
+    pub daily_best: Option<DailyHighScore>,
+    pub daily_txt: Option<Text>,
+    pub speedrun_timer: Option<SpeedRunTimer>,
+    pub speedrun_records: SpeedRunRecords,
+    pub stats: SessionStats,
+    pub achievements: AchievementTracker,
+    pub stats_txt: Text,
+    pub max_length: f32,
+    pub spatial_hash: SpatialHash,
+    pub static_layer: Option<Canvas>,
+    pub total_moves: u64,
+    pub total_turns: u64,
+    pub new_record_txt: Text,
+    pub new_record_timer: f32,
+    pub is_new_record: bool,
+    pub combo: u32,
+    pub combo_timer: f32,
+    pub combo_txt: Text,
+    pub difficulty: DifficultyLevel,
+    pub speed: f32,
+    pub secs_per_input_update: f32,
+    pub difficulty_txt: Text,
+    pub pregame_difficulty_txt: Text,
+    pub wrap_walls: bool,
+    pub wrap_walls_txt: Text,
+    pub power_ups: PowerUpState,
+    pub hunger_timer: f32,
+    pub hunger_rate: f32,
+    pub lives: u32,
+    pub respawn_timer: f32,
+    pub mode: GameMode,
+    pub remaining_time: f32,
+    pub survival: SurvivalState,
+    pub shrink_border: ShrinkBorder,
+    pub ai: AiController,
+    pub autoplay_txt: Text,
+    pub fixed_seed: Option<u64>,
+    pub seed: u64,
+    pub rng: SmallRng,
+    pub run_time: f32,
+    pub record_next: bool,
+    pub record_txt: Text,
+    pub recorder: Option<ReplayRecorder>,
+    pub player: Option<ReplayPlayer>,
+    pub fullscreen: bool,
+    pub frame_stats: Option<FrameStats>,
+    pub theme: ColorTheme,
+    pub theme2: ColorTheme,
+    pub palettes: Vec<ColorPalette>,
+    pub current_palette: Option<usize>,
+    pub particles: ParticleSystem,
+    pub popups: Vec<ScorePopup>,
+    pub notice: Option<ScorePopup>,
+    pub event_queue: Vec<GameEvent>,
+    pub hue_offset: f32,
+    pub shake_timer: f32,
+    pub shake_magnitude: f32,
+    pub menu: MenuData,
+    pub settings: SettingsMenu,
+    pub config_path: PathBuf,
+    pub music_track: Option<MusicTrack>,
+    pub crossfade_timer: f32,
+    pub muted: bool,
+    pub show_grid: bool,
     pub state: GameState,
     pub resources: ResourceLoader,
+    pub strings: Strings,
 }
 
 impl GameData {
     /// Creates new `GameData` instance. Loads game resources.
+    /// The game starts in the main menu.
+    ///
+    /// Snake is created on the middle of the screen
+    /// and moves with a speed from `config`, scaled by the difficulty
+    /// from `args`, or from `config` when `args` do not give one.
+    /// Every run is played with the rules of the given `mode`.
+    ///
+    /// When `args` contain a seed, every run places food in the same way.
+    /// Otherwise each run gets its own random seed.
+    ///
+    /// Levels are read from `consts::LEVELS_DIR`. Without any level files
+    /// the game is played on a single level with `consts::DEFAULT_OBSTACLES`.
     ///
-    /// Snake is created on the middle of the screen.
+    /// Fails when any of the bundled resources cannot be loaded.
     ///
-    pub fn new(ctx: &mut Context) -> Self {
+    pub fn new(
+        ctx: &mut Context,
+        config: &GameConfig,
+        args: &StartupArgs,
+        mode: GameMode,
+    ) -> GameResult<Self> {
+        let difficulty = args.difficulty.unwrap_or(config.difficulty);
+        let seed = args.seed;
         graphics::set_default_filter(ctx, graphics::FilterMode::Nearest);
-        let resources = ResourceLoader::new(ctx);
-        Self {
-            snake: Snake::new(consts::SCREEN_SIZE.x / 2.0, consts::SCREEN_SIZE.y / 2.0),
+        let mut resources = ResourceLoader::new(ctx)?;
+        if let Some(path) = &config.font_path {
+            match ResourceLoader::load_custom_font(ctx, path) {
+                Ok(font) => resources.font = font,
+                Err(err) => eprintln!("{}, using the bundled font", err),
+            }
+        }
+        let strings = ResourceLoader::load_strings(&config.language);
+        let key_bindings = KeyBindings::from_config(config);
+        let highscore = HighScoreManager::new();
+        let stats = SessionStats::load();
+        let run_seed = seed.unwrap_or_else(rand::random);
+        let screen = Coords::new(config.screen_width, config.screen_height);
+        let mut levels = Level::load_all(Path::new(consts::LEVELS_DIR));
+        if levels.is_empty() {
+            levels.push(Level::fallback(config.max_food));
+        }
+        let mut data = Self {
+            config: config.clone(),
+            screen,
+            snake: Snake::new(screen.x / 2.0, screen.y / 2.0),
+            two_player: args.two_player,
+            snake2: None,
+            obstacles: Vec::new(),
+            portals: Vec::new(),
+            board: Board::new(screen, consts::SNAKE_WIDTH),
+            ice_slide: 0,
+            ice_slide2: 0,
+            levels,
+            current_level: 0,
+            level_speed_mult: 1.,
+            level_clear_timer: 0.,
+            level_txt: Self::create_level_txt(1, &strings, resources.font),
+            level_clear_txt: Self::create_level_clear_txt(1, &strings, resources.font),
+            inputs2: VecDeque::new(),
+            input_timer2: 0.,
+            score2: 0,
+            score2_txt: Self::create_score2_txt(0, &strings, resources.font),
+            two_player_txt: Self::create_two_player_txt(
+                args.two_player,
+                &strings,
+                &key_bindings,
+                resources.font,
+            ),
             delta_time: Instant::now(),
-            food: Food::random(),
+            accumulator: 0.,
+            foods: Vec::new(),
+            max_food: config.max_food,
             inputs: VecDeque::new(),
+            input_source: InputSource::default(),
+            key_bindings: key_bindings.clone(),
             input_timer: 0.0,
             score: 0,
-            score_txt: Self::create_score_txt(0, resources.font),
-            pregame_txt: Self::create_pregame_txt(resources.font),
-            state: GameState::PreGame,
+            score_txt: Self::create_score_txt(0, &strings, resources.font),
+            pregame_txt: Self::create_pregame_txt(&strings, &key_bindings, resources.font),
+            paused_txt: Self::create_paused_txt(&strings, resources.font),
+            game_over_txt: Self::create_game_over_txt(&strings, resources.font),
+            last_death: None,
+            death_txt: Self::create_death_txt(None, &strings, resources.font),
+            final_score: 0,
+            final_score_txt: Self::create_final_score_txt(0, &strings, resources.font),
+            highscore_txt: Self::create_highscore_txt(highscore.best(), &strings, resources.font),
+            highscore,
+            daily_best: None,
+            daily_txt: None,
+            speedrun_timer: None,
+            speedrun_records: SpeedRunRecords::load(),
+            stats_txt: Self::create_stats_txt(&stats, &strings, resources.font),
+            stats,
+            achievements: AchievementTracker::load(),
+            max_length: 0.,
+            spatial_hash: SpatialHash::default(),
+            static_layer: None,
+            total_moves: 0,
+            total_turns: 0,
+            new_record_txt: Self::create_new_record_txt(&strings, resources.font),
+            new_record_timer: 0.,
+            is_new_record: false,
+            combo: 0,
+            combo_timer: 0.,
+            combo_txt: Self::create_combo_txt(1, &strings, resources.font),
+            difficulty,
+            speed: config.snake_speed * difficulty.speed_multiplier(),
+            secs_per_input_update: config.secs_per_input_update / difficulty.speed_multiplier(),
+            difficulty_txt: Self::create_difficulty_txt(difficulty, &strings, resources.font),
+            pregame_difficulty_txt: Self::create_pregame_difficulty_txt(
+                difficulty,
+                &strings,
+                &key_bindings,
+                resources.font,
+            ),
+            wrap_walls: false,
+            wrap_walls_txt: Self::create_wrap_walls_txt(
+                false,
+                &strings,
+                &key_bindings,
+                resources.font,
+            ),
+            power_ups: PowerUpState::default(),
+            hunger_timer: config.hunger_rate,
+            hunger_rate: config.hunger_rate,
+            lives: config.lives,
+            respawn_timer: 0.,
+            mode,
+            remaining_time: mode.time_limit().unwrap_or(0.),
+            survival: SurvivalState::default(),
+            shrink_border: ShrinkBorder::default(),
+            ai: AiController::default(),
+            autoplay_txt: Self::create_autoplay_txt(&strings, &key_bindings, resources.font),
+            fixed_seed: seed,
+            seed: run_seed,
+            rng: SmallRng::seed_from_u64(run_seed),
+            run_time: 0.,
+            record_next: false,
+            record_txt: Self::create_record_txt(false, &strings, &key_bindings, resources.font),
+            recorder: None,
+            player: None,
+            fullscreen: args.fullscreen,
+            frame_stats: debug::create_frame_stats(),
+            theme: ColorTheme::from_name(config.theme),
+            theme2: ColorTheme::from_name(config.theme).player_two(),
+            palettes: ColorPalette::load_all(Path::new(consts::PALETTES_DIR)),
+            current_palette: None,
+            particles: ParticleSystem::default(),
+            popups: Vec::new(),
+            notice: None,
+            event_queue: Vec::new(),
+            hue_offset: 0.,
+            shake_timer: 0.,
+            shake_magnitude: 0.,
+            menu: MenuData::new(&strings, resources.font),
+            settings: SettingsMenu::new(&strings, &key_bindings, resources.font),
+            music_track: None,
+            crossfade_timer: 0.,
+            muted: false,
+            // grid helps to see the snake alignment while debugging
+            show_grid: cfg!(feature = "debug"),
+            config_path: args
+                .config
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(consts::CONFIG_FILE)),
+            state: GameState::Menu,
             resources,
+            strings,
+        };
+        data.apply_sfx_volume();
+        if data.fullscreen {
+            let (width, height) = graphics::drawable_size(ctx);
+            data.on_resize(width, height);
         }
+        if let GameMode::SpeedRun { target_score } = mode {
+            let best = data.speedrun_records.top(target_score).first().copied();
+            data.highscore_txt =
+                Self::create_best_time_txt(best, &data.strings, data.resources.font);
+        }
+        data.restart(run_seed);
+        if args.replay.is_none() {
+            data.load_autosave();
+        }
+        Ok(data)
     }
 
-    fn reset(&mut self) {
-        self.snake = Snake::new(consts::SCREEN_SIZE.x / 2.0, consts::SCREEN_SIZE.y / 2.0);
-        self.food = Food::random();
-        while self.snake.collide(&self.food.bbox) {
-            self.food = Food::random();
+    /// Creates new `GameData` instance that plays back the replay stored in `path`.
+    ///
+    pub fn new_playback(ctx: &mut Context, config: &GameConfig, path: &Path) -> GameResult<Self> {
+        let replay = Replay::load(path)?;
+        let args = StartupArgs {
+            seed: None,
+            difficulty: Some(replay.difficulty),
+            mode: "classic".to_string(),
+            two_player: false,
+            config: None,
+            fullscreen: false,
+            headless: None,
+            benchmark: false,
+            replay: Some(path.to_path_buf()),
+        };
+        let mut data = Self::new(ctx, config, &args, GameMode::Classic)?;
+        data.start_playback(replay);
+        Ok(data)
+    }
+
+    /// Creates new `GameData` instance for `GameMode::DailyChallenge`,
+    /// seeded with the current UTC date, so everyone gets the same food placement that day.
+    /// The best score shown is the one of today's challenge.
+    ///
+    pub fn new_daily(
+        ctx: &mut Context,
+        config: &GameConfig,
+        args: &StartupArgs,
+    ) -> GameResult<Self> {
+        let date = daily::today_utc();
+        let seed = daily::seed_of(date);
+        let args = StartupArgs {
+            seed: Some(seed),
+            ..args.clone()
+        };
+        let mut data = Self::new(ctx, config, &args, GameMode::DailyChallenge)?;
+        let best = DailyHighScore::load(seed);
+        data.highscore_txt =
+            Self::create_highscore_txt(best.best, &data.strings, data.resources.font);
+        data.daily_txt = Some(Self::create_daily_txt(
+            date,
+            &data.strings,
+            data.resources.font,
+        ));
+        data.daily_best = Some(best);
+        Ok(data)
+    }
+
+    /// Starts the game from the pre-game screen.
+    /// When recording was requested, the run is recorded from now on.
+    ///
+    pub fn start_game(&mut self) {
+        if self.state != GameState::PreGame {
+            return;
+        }
+
+        if self.record_next {
+            self.recorder = Some(ReplayRecorder::new(
+                self.seed,
+                self.difficulty,
+                self.wrap_walls,
+            ));
+        }
+        if let GameMode::SpeedRun { .. } = self.mode {
+            self.speedrun_timer = Some(SpeedRunTimer::start());
+        }
+        self.state = GameState::Game;
+    }
+
+    /// Ends a speed run once the score reaches its target,
+    /// storing the time among the records of that target.
+    ///
+    fn check_speedrun_finish(&mut self) {
+        let target_score = match self.mode {
+            GameMode::SpeedRun { target_score } if self.score >= target_score => target_score,
+            _ => return,
+        };
+        let time_ms = match &self.speedrun_timer {
+            Some(timer) => timer.elapsed_ms(),
+            None => return,
+        };
+
+        if self.player.is_none() {
+            let record = SpeedRunRecord {
+                target_score,
+                time_ms,
+            };
+            if self.speedrun_records.add(record) == Some(0) {
+                self.is_new_record = true;
+            }
+            self.speedrun_records.save();
+        }
+        self.game_over();
+        self.death_txt = Text::new(
+            TextFragment::new(i18n::fill(
+                &self.strings.speedrun_completed,
+                &[&speedrun::format_time(time_ms)],
+            ))
+            .scale(graphics::Scale::uniform(32.))
+            .font(self.resources.font),
+        );
+    }
+
+    /// Restarts the game with settings of the given replay
+    /// and feeds its recorded inputs back in.
+    ///
+    pub fn start_playback(&mut self, replay: Replay) {
+        self.set_difficulty(replay.difficulty);
+        if self.wrap_walls != replay.wrap_walls {
+            self.toggle_wrap_walls();
+        }
+        self.restart(replay.seed);
+        self.recorder = None;
+        self.player = Some(ReplayPlayer::new(replay));
+        self.state = GameState::Game;
+    }
+
+    /// Plays back the last recorded run, if there is one.
+    ///
+    pub fn play_last_replay(&mut self) {
+        let replay = Replay::last_replay_path().map(|path| Replay::load(&path));
+        match replay {
+            Some(Ok(replay)) => self.start_playback(replay),
+            Some(Err(err)) => eprintln!("{}", err),
+            None => eprintln!("Could not determine the replay location"),
+        }
+    }
+
+    /// Switches between `GameMode::AutoPlay` and `GameMode::Classic`.
+    ///
+    pub fn toggle_autoplay(&mut self) {
+        if let GameMode::DailyChallenge | GameMode::SpeedRun { .. } = self.mode {
+            return;
+        }
+        self.mode = if self.mode == GameMode::AutoPlay {
+            GameMode::Classic
+        } else {
+            GameMode::AutoPlay
+        };
+        // leaving the maze mode has to bring back the obstacles of the level
+        self.restart(self.seed);
+    }
+
+    /// Mutes or unmutes all sound effects and music.
+    ///
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.apply_sfx_volume();
+    }
+
+    fn apply_sfx_volume(&mut self) {
+        let volume = if self.muted {
+            0.
+        } else {
+            self.config.sfx_volume * self.config.master_volume
+        };
+        self.resources.set_sfx_volume(volume);
+    }
+
+    /// Plays the music track that fits the current state: menu music
+    /// on the menu screens, game music during the game and silence when
+    /// the game is paused or over.
+    ///
+    /// When the track changes, the old one fades out while the new one fades in,
+    /// over `consts::CROSSFADE_SECS`. Paused game music continues
+    /// from the same place on resume.
+    ///
+    pub fn update_music(&mut self, time_delta: f32) {
+        let wanted = match self.state {
+            GameState::Menu | GameState::Settings | GameState::PreGame => Some(MusicTrack::Menu),
+            GameState::Game | GameState::Respawning => Some(MusicTrack::Game),
+            GameState::Paused | GameState::GameOver => None,
+        };
+        if wanted != self.music_track {
+            let prev = std::mem::replace(&mut self.music_track, wanted);
+            match wanted {
+                Some(track) => {
+                    let source = self.resources.music_source(track);
+                    if source.paused() {
+                        source.resume();
+                    } else if !source.playing() {
+                        if let Err(err) = source.play() {
+                            eprintln!("{}", err);
+                        }
+                    }
+                    if prev.is_some() {
+                        self.crossfade_timer = consts::CROSSFADE_SECS;
+                    }
+                }
+                None => self.resources.music.pause(),
+            }
+        }
+
+        self.crossfade_timer = f32::max(self.crossfade_timer - time_delta, 0.);
+        let fade = maths::apply_easing(
+            self.crossfade_timer / consts::CROSSFADE_SECS,
+            maths::ease_in_out_quad,
+        );
+        let volume = if self.muted {
+            0.
+        } else {
+            self.config.music_volume * self.config.master_volume
+        };
+        for track in [MusicTrack::Menu, MusicTrack::Game] {
+            let is_current = self.music_track == Some(track);
+            let source = self.resources.music_source(track);
+            if is_current {
+                source.set_volume(volume * (1. - fade));
+            } else if fade > 0. {
+                source.set_volume(volume * fade);
+            } else if source.playing() {
+                source.stop();
+            }
+        }
+    }
+
+    /// Toggles recording of the next run.
+    ///
+    pub fn toggle_recording(&mut self) {
+        self.record_next = !self.record_next;
+        self.record_txt = Self::create_record_txt(
+            self.record_next,
+            &self.strings,
+            &self.key_bindings,
+            self.resources.font,
+        );
+    }
+
+    fn save_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            let saved = Replay::last_replay_path().map(|path| recorder.into_replay().save(&path));
+            match saved {
+                Some(Ok(())) => {}
+                Some(Err(err)) => eprintln!("{}", err),
+                None => eprintln!("Could not determine the replay location"),
+            }
+        }
+    }
+
+    /// Changes the difficulty, along with the snake speed and the input rate.
+    ///
+    pub fn set_difficulty(&mut self, difficulty: DifficultyLevel) {
+        self.difficulty = difficulty;
+        self.speed = self.config.snake_speed * difficulty.speed_multiplier();
+        self.secs_per_input_update =
+            self.config.secs_per_input_update / difficulty.speed_multiplier();
+        self.difficulty_txt =
+            Self::create_difficulty_txt(difficulty, &self.strings, self.resources.font);
+        self.pregame_difficulty_txt = Self::create_pregame_difficulty_txt(
+            difficulty,
+            &self.strings,
+            &self.key_bindings,
+            self.resources.font,
+        );
+    }
+
+    /// Stores the final score and waits in `GameState::GameOver`
+    /// until the player presses a key.
+    ///
+    /// In `GameMode::Survival` the final score is the number of seconds survived.
+    /// Run of the first player is added to the session stats, unless it was a replay.
+    /// Cause of the death has to be stored in `last_death` beforehand.
+    ///
+    fn game_over(&mut self) {
+        self.death_txt =
+            Self::create_death_txt(self.last_death.as_ref(), &self.strings, self.resources.font);
+        if self.two_player {
+            self.final_score = self.score;
+            self.final_score_txt = Self::create_two_player_final_score_txt(
+                self.score,
+                self.score2,
+                &self.strings,
+                self.resources.font,
+            );
+        } else if self.mode == GameMode::Survival {
+            self.final_score = self.survival.elapsed as u32;
+            self.final_score_txt = Self::create_survival_time_txt(
+                self.final_score,
+                &self.strings,
+                self.resources.font,
+            );
+        } else {
+            self.final_score = self.score;
+            self.final_score_txt =
+                Self::create_final_score_txt(self.final_score, &self.strings, self.resources.font);
+        }
+        let replaying = self.player.is_some();
+        if let Some(daily_best) = self.daily_best.as_mut().filter(|_| !replaying) {
+            if daily_best.record(self.final_score) {
+                self.is_new_record = true;
+                self.highscore_txt = Self::create_highscore_txt(
+                    self.final_score,
+                    &self.strings,
+                    self.resources.font,
+                );
+            }
+        }
+        if self.player.is_none() {
+            self.stats.record_run(
+                self.final_score,
+                self.max_length,
+                self.total_moves,
+                self.total_turns,
+            );
+            self.stats.save();
+            self.stats_txt =
+                Self::create_stats_txt(&self.stats, &self.strings, self.resources.font);
+        }
+        self.inputs.clear();
+        self.save_recording();
+        self.remove_autosave();
+        self.shake_timer = consts::SHAKE_SECS;
+        self.shake_magnitude = consts::SHAKE_MAGNITUDE;
+        self.state = GameState::GameOver;
+    }
+
+    /// Takes one life away from the first player after its snake died.
+    /// While some are left, the snake respawns after `consts::RESPAWN_SECS`,
+    /// otherwise the run is over.
+    ///
+    fn lose_life(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 || self.two_player {
+            self.game_over();
+            return;
+        }
+        self.inputs.clear();
+        self.shake_timer = consts::SHAKE_SECS;
+        self.shake_magnitude = consts::SHAKE_MAGNITUDE;
+        self.respawn_timer = consts::RESPAWN_SECS;
+        self.state = GameState::Respawning;
+    }
+
+    /// Counts down the break after a lost life, then places a fresh snake
+    /// in the middle of the screen. Score, food and the level stay as they were.
+    ///
+    pub fn update_respawn(&mut self, time_delta: f32) {
+        self.update_effects(time_delta);
+        self.respawn_timer = f32::max(self.respawn_timer - time_delta, 0.);
+        if self.respawn_timer <= 0. {
+            self.snake = Snake::new(self.screen.x / 2.0, self.screen.y / 2.0);
+            self.hunger_timer = self.hunger_rate;
+            self.accumulator = 0.;
+            self.state = GameState::Game;
+        }
+    }
+
+    /// Leaves the `GameState::GameOver` screen and prepares a fresh game.
+    ///
+    pub fn confirm_game_over(&mut self) {
+        if self.state == GameState::GameOver {
+            self.reset();
+        }
+    }
+
+    /// Toggles the wrap-around mode, where the snake passes through the walls.
+    ///
+    pub fn toggle_wrap_walls(&mut self) {
+        self.wrap_walls = !self.wrap_walls;
+        self.wrap_walls_txt = Self::create_wrap_walls_txt(
+            self.wrap_walls,
+            &self.strings,
+            &self.key_bindings,
+            self.resources.font,
+        );
+    }
+
+    /// Switches between the desktop fullscreen and the windowed mode.
+    /// New screen size is applied once ggez reports the resize.
+    ///
+    pub fn toggle_fullscreen(&mut self, ctx: &mut Context) {
+        let fullscreen_type = if self.fullscreen {
+            FullscreenType::Windowed
+        } else {
+            FullscreenType::Desktop
+        };
+        match graphics::set_fullscreen(ctx, fullscreen_type) {
+            Ok(()) => self.fullscreen = !self.fullscreen,
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    /// Updates the screen size used for wall collisions, food placement and the HUD.
+    ///
+    /// Snake and food are moved inside the new bounds, so nothing ends up
+    /// off-screen or outside the walls.
+    ///
+    pub fn on_resize(&mut self, new_width: f32, new_height: f32) {
+        self.screen = Coords::new(new_width, new_height);
+        self.static_layer = None;
+        self.snake.clamp_to(self.screen);
+        for food in &mut self.foods {
+            food.clamp_to(self.screen);
+        }
+    }
+
+    /// Prepares a fresh run, with the random number generator restarted from `seed`.
+    ///
+    fn restart(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SmallRng::seed_from_u64(seed);
+        self.run_time = 0.;
+        self.accumulator = 0.;
+        self.spawn_snakes();
+        self.foods.clear();
+        // food is placed inside the border, so it has to be reset before the level is loaded
+        self.shrink_border = ShrinkBorder::default();
+        if self.mode == GameMode::Maze {
+            self.build_maze();
         }
+        self.current_level = 0;
+        self.level_clear_timer = 0.;
+        self.load_current_level();
         self.inputs.clear();
+        self.inputs2.clear();
+        self.last_death = None;
         self.score = 0;
-        self.score_txt = Self::create_score_txt(0, self.resources.font);
+        self.score_txt = Self::create_score_txt(0, &self.strings, self.resources.font);
+        self.max_length = self.snake.length();
+        self.total_moves = 0;
+        self.total_turns = 0;
+        self.score2 = 0;
+        self.score2_txt = Self::create_score2_txt(0, &self.strings, self.resources.font);
+        self.new_record_timer = 0.;
+        self.is_new_record = false;
+        self.set_combo(0);
+        self.remaining_time = self.mode.time_limit().unwrap_or(0.);
+        self.survival = SurvivalState::default();
+        self.power_ups = PowerUpState::default();
+        self.hunger_timer = self.hunger_rate;
+        self.lives = self.config.lives;
+        self.respawn_timer = 0.;
+        self.particles.clear();
+        self.popups.clear();
+        self.shake_timer = 0.;
+    }
+
+    /// Places the snake in the middle of the screen,
+    /// or both snakes side by side in the two-player mode.
+    ///
+    fn spawn_snakes(&mut self) {
+        if self.two_player {
+            self.snake = Snake::new(self.screen.x / 3.0, self.screen.y / 2.0);
+            self.snake2 = Some(Snake::new(self.screen.x * 2. / 3.0, self.screen.y / 2.0));
+        } else {
+            self.snake = Snake::new(self.screen.x / 2.0, self.screen.y / 2.0);
+            self.snake2 = None;
+        }
+    }
+
+    /// Generates a new maze, seeded from the run rng, and turns its walls into obstacles.
+    /// Corridors in front of the snakes are carved out, so they do not start facing a wall.
+    ///
+    fn build_maze(&mut self) {
+        let width = (self.screen.x / consts::MAZE_CELL_SIZE) as usize;
+        let height = (self.screen.y / consts::MAZE_CELL_SIZE) as usize;
+        self.board = board::generate_maze(width, height, self.rng.gen());
+        self.static_layer = None;
+        for snake in std::iter::once(&self.snake).chain(self.snake2.as_ref()) {
+            let top = snake.tail_position();
+            let start_area = Rect::new(
+                top.x - consts::SNAKE_HALF_WIDTH,
+                top.y,
+                consts::SNAKE_WIDTH,
+                consts::SNAKE_START_HEIGHT + consts::MAZE_CELL_SIZE * 2.,
+            );
+            self.board.fill(start_area, TileKind::Normal);
+        }
+        self.obstacles = self
+            .board
+            .areas_of(TileKind::Wall)
+            .into_iter()
+            .map(|bbox| Obstacle::new(bbox, consts::MAZE_WALL_NAME))
+            .collect();
+    }
+
+    /// Toggles the two-player mode and places the snakes for it.
+    ///
+    pub fn toggle_two_player(&mut self) {
+        self.two_player = !self.two_player;
+        self.two_player_txt = Self::create_two_player_txt(
+            self.two_player,
+            &self.strings,
+            &self.key_bindings,
+            self.resources.font,
+        );
+        self.restart(self.seed);
+    }
+
+    fn reset(&mut self) {
+        self.remove_autosave();
+        let seed = self.fixed_seed.unwrap_or_else(rand::random);
+        self.restart(seed);
+        self.recorder = None;
+        self.player = None;
+        self.speedrun_timer = None;
         self.state = GameState::PreGame;
     }
 
-    fn inc_score(&mut self) {
-        self.score += 1;
-        self.score_txt = Self::create_score_txt(self.score, self.resources.font);
+    /// Spawns new food items until there are `max_food` of them.
+    ///
+    /// Food is placed with `Food::spawn_away_from`, like in `Simulation`, and only where
+    /// `is_area_free` allows, so it never lies on a snake, on obstacles, on portals,
+    /// in the void, in the hazard border or on top of other food.
+    /// When no place is left, fewer food items stay on the screen.
+    ///
+    fn refill_food(&mut self) {
+        while self.foods.len() < self.max_food {
+            // the closure borrows the whole state, so a copy of the rng is advanced and written back
+            let mut rng = self.rng.clone();
+            let food = Food::spawn_away_from(
+                &mut rng,
+                self.screen,
+                self.shrink_border.margin,
+                self.config.food_max_age,
+                &self.snake,
+                consts::FOOD_MIN_HEAD_DISTANCE,
+                |food| !self.is_area_free(food.bbox),
+            );
+            self.rng = rng;
+            match food {
+                Some(food) => self.foods.push(food),
+                None => break,
+            }
+        }
     }
 
-    fn create_score_txt(score: u32, font: Font) -> Text {
-        Text::new(
-            TextFragment::new(format!(SCORE_FMT!(), score))
-                .scale(graphics::Scale::uniform(24.))
-                .font(font),
-        )
+    /// Returns the middle of a random free cell of the given size, that a portal could be put in.
+    /// The cells are searched with `sim::find_free_cell`, the same way `Food::spawn_away_from`
+    /// falls back to, the hazard border is left out.
+    ///
+    /// Cells at least `consts::FOOD_MIN_HEAD_DISTANCE` away from the head of the first snake
+    /// are preferred, the ones nearer are used only when nothing else is free.
+    /// Returns `None` when the whole board is occupied.
+    ///
+    pub fn find_free_cell(&mut self, size: Coords) -> Option<Coords> {
+        // the closure borrows the whole state, so a copy of the rng is advanced and written back
+        let mut rng = self.rng.clone();
+        let cell = sim::find_free_cell(
+            &mut rng,
+            self.screen,
+            self.shrink_border.margin,
+            size,
+            self.snake.head_position(),
+            consts::FOOD_MIN_HEAD_DISTANCE,
+            |area| self.is_area_free(area),
+        );
+        self.rng = rng;
+        cell
     }
-    fn create_pregame_txt(font: Font) -> Text {
-        Text::new(
-            TextFragment::new(consts::PREGAME_TXT)
-                .scale(graphics::Scale::uniform(64.))
-                .font(font),
-        )
+
+    /// Moves portals whose entry lies under a snake or an obstacle to a free cell found
+    /// with `find_free_cell`. The exit of the partner portal, that leads next to the moved entry,
+    /// is moved along, so the pair stays connected.
+    ///
+    fn place_blocked_portals(&mut self) {
+        for idx in 0..self.portals.len() {
+            let entry = self.portals[idx].entry;
+            let blocked = self.snake.collide(&entry)
+                || self
+                    .snake2
+                    .as_ref()
+                    .is_some_and(|snake2| snake2.collide(&entry))
+                || self
+                    .obstacles
+                    .iter()
+                    .any(|obstacle| obstacle.bbox.overlaps(&entry));
+            if !blocked {
+                continue;
+            }
+            let old_center = Coords::new(entry.x + entry.w / 2., entry.y + entry.h / 2.);
+            let new_center = match self.find_free_cell(Coords::new(entry.w, entry.h)) {
+                Some(center) => center,
+                None => continue,
+            };
+
+            let offset = new_center - old_center;
+            let moved = &mut self.portals[idx].entry;
+            moved.x += offset.x;
+            moved.y += offset.y;
+            let partner = self
+                .portals
+                .iter_mut()
+                .enumerate()
+                .filter(|&(other, _)| other != idx)
+                .min_by(|(_, a), (_, b)| {
+                    a.exit_pos
+                        .distance(&old_center)
+                        .total_cmp(&b.exit_pos.distance(&old_center))
+                });
+            if let Some((_, partner)) = partner {
+                partner.exit_pos += offset;
+            }
+        }
     }
 
-    /// Processes user input, capped to `consts::SECS_PER_INPUT_UPDATE`.
+    /// Checks if `area` lies off both snakes, obstacles, portals, the void and all food
     ///
-    /// The cap is there to make sure that 180 turns always makes enough
-    /// space between both parts of the snake.
+    fn is_area_free(&self, area: Rect) -> bool {
+        let center = Coords::new(area.x + area.w / 2., area.y + area.h / 2.);
+        !self
+            .snake
+            .get_all_bboxes()
+            .iter()
+            .any(|bbox| bbox.overlaps(&area))
+            && !self
+                .snake2
+                .as_ref()
+                .is_some_and(|snake2| snake2.collide(&area))
+            && !self
+                .obstacles
+                .iter()
+                .any(|obstacle| obstacle.bbox.overlaps(&area))
+            && !self
+                .portals
+                .iter()
+                .any(|portal| portal.entry.overlaps(&area))
+            && self.board.tile_at(center) != TileKind::Void
+            && !self.foods.iter().any(|food| food.bbox.overlaps(&area))
+    }
+
+    /// Configures the speed, obstacles, portals, terrain and food count of the given level,
+    /// without restarting the snake.
     ///
-    pub fn update_input(&mut self, time_delta: f32) {
-        self.input_timer += time_delta;
-        if self.input_timer < consts::SECS_PER_INPUT_UPDATE {
+    /// Obstacles and void that would appear under a snake are left out,
+    /// portals that would appear there are moved to a free place,
+    /// food lying on the new obstacles is placed once again.
+    /// Food count of the level takes the place of `GameConfig::max_food`.
+    ///
+    /// In `GameMode::Maze` the maze replaces obstacles and terrain of the level,
+    /// and portals are left out, as they could lead into a wall.
+    ///
+    pub fn load_level(&mut self, level: &Level) {
+        self.level_speed_mult = level.speed_mult;
+        self.static_layer = None;
+        self.max_food = level.food_count;
+        self.level_txt = Self::create_level_txt(level.id, &self.strings, self.resources.font);
+        if self.mode == GameMode::Maze {
+            self.portals.clear();
+            self.retain_placeable_food();
             return;
         }
 
-        if let Some((idx, &new_dir)) =
-            it::rev(&self.inputs).find_position(|dir| !dir.is_colinear(self.snake.dir))
+        let snake = &self.snake;
+        let snake2 = self.snake2.as_ref();
+        self.obstacles = level
+            .obstacles
+            .iter()
+            .filter(|obstacle| {
+                !snake.collide(&obstacle.bbox)
+                    && !snake2.is_some_and(|snake2| snake2.collide(&obstacle.bbox))
+            })
+            .cloned()
+            .collect();
+        self.portals = level.portals.clone();
+        self.board = Board::new(self.screen, consts::SNAKE_WIDTH);
+        for &(kind, area) in &level.terrain {
+            let under_snake = self.snake.collide(&area)
+                || self
+                    .snake2
+                    .as_ref()
+                    .is_some_and(|snake2| snake2.collide(&area));
+            if kind != TileKind::Void || !under_snake {
+                self.board.fill(area, kind);
+            }
+        }
+        self.place_blocked_portals();
+        self.retain_placeable_food();
+    }
+
+    /// Drops food that lies on obstacles or portals, or does not fit in `max_food`,
+    /// then places the missing food once again.
+    ///
+    fn retain_placeable_food(&mut self) {
+        let (obstacles, portals) = (&self.obstacles, &self.portals);
+        self.foods.retain(|food| {
+            !obstacles
+                .iter()
+                .any(|obstacle| obstacle.bbox.overlaps(&food.bbox))
+                && !portals
+                    .iter()
+                    .any(|portal| portal.entry.overlaps(&food.bbox))
+        });
+        self.foods.truncate(self.max_food);
+        self.refill_food();
+    }
+
+    fn load_current_level(&mut self) {
+        let level = self.levels[self.current_level as usize].clone();
+        self.load_level(&level);
+    }
+
+    /// Starts the level-clear animation, when the score reached the milestone
+    /// of the current level and there is a next one.
+    /// Next level is loaded once the animation ends.
+    ///
+    fn check_level_progress(&mut self) {
+        let next = self.current_level as usize + 1;
+        if self.level_clear_timer > 0.
+            || next >= self.levels.len()
+            || self.score < next as u32 * consts::LEVEL_SCORE_STEP
         {
-            let truncated_len = self.inputs.len() - idx - 1;
-            self.inputs.truncate(truncated_len);
+            return;
+        }
 
-            self.snake.dir = new_dir;
-            self.input_timer = 0.;
-        } else {
-            self.inputs.clear();
+        let cleared_id = self.levels[self.current_level as usize].id;
+        self.level_clear_txt =
+            Self::create_level_clear_txt(cleared_id, &self.strings, self.resources.font);
+        self.level_clear_timer = consts::LEVEL_CLEAR_SECS;
+        self.current_level += 1;
+        self.event_queue.push(GameEvent::LevelComplete);
+    }
+
+    /// Returns how far the level-clear animation is, between 0 and 1.
+    /// Without the animation it is 0.
+    ///
+    pub fn level_clear_progress(&self) -> f32 {
+        if self.level_clear_timer <= 0. {
+            return 0.;
         }
+        1. - self.level_clear_timer / consts::LEVEL_CLEAR_SECS
     }
 
-    /// Updates snake (collision, movement, growth)
-    /// Upon collision with anything (self, wall, food) takes proper action.
+    /// Draws the level-clear animation: the screen fades out
+    /// under the text saying which level was cleared.
     ///
-    pub fn update_snake(&mut self, time_delta: f32) {
-        if self.snake.collide(&self.food.bbox) {
-            self.snake.grow(consts::FOOD_SIZE);
-            self.inc_score();
-            while self.snake.collide(&self.food.bbox) {
-                self.food = Food::random();
+    pub fn draw_level_clear(&self, ctx: &mut Context) {
+        if self.level_clear_timer <= 0. {
+            return;
+        }
+
+        let fade = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0., 0., self.screen.x, self.screen.y),
+            graphics::Color::new(0., 0., 0., 0.5 * self.level_clear_progress()),
+        )
+        .unwrap();
+        graphics::draw(ctx, &fade, graphics::DrawParam::default())
+            .expect("Error while drawing level-clear fade");
+        Renderer::draw_centered_text_with_outline(ctx, &self.level_clear_txt, 0., &self.theme);
+    }
+
+    /// Returns the score multiplier of the current combo
+    ///
+    pub fn combo_multiplier(&self) -> u32 {
+        sim::combo_multiplier(self.combo)
+    }
+
+    fn set_combo(&mut self, combo: u32) {
+        self.combo = combo;
+        self.combo_timer = if combo > 0 { consts::COMBO_SECS } else { 0. };
+        self.combo_txt =
+            Self::create_combo_txt(self.combo_multiplier(), &self.strings, self.resources.font);
+    }
+
+    /// Adds points to the score and returns how many were actually added,
+    /// after power-up multipliers.
+    ///
+    fn inc_score(&mut self, points: u32) -> u32 {
+        let points = self.power_ups.multiply_score(points);
+        self.score += points;
+        self.score_txt = Self::create_score_txt(self.score, &self.strings, self.resources.font);
+
+        // daily challenge records its own best score only after death, speed run records times
+        let all_time = self.daily_best.is_none() && !matches!(self.mode, GameMode::SpeedRun { .. });
+        if all_time && self.highscore.is_new_record(self.score) {
+            if !self.is_new_record {
+                self.is_new_record = true;
+                self.new_record_timer = consts::NEW_RECORD_FLASH_SECS;
             }
-        } else if self.snake.self_collide() || self.snake.wall_collide() {
-            self.reset();
-        } else {
-            self.snake.do_move(time_delta * consts::SPEED);
+            self.highscore.save(self.score);
+            self.highscore_txt =
+                Self::create_highscore_txt(self.score, &self.strings, self.resources.font);
         }
+
+        points
+    }
+
+    /// Scores longer than 5 digits get smaller text, so they still fit in the HUD
+    ///
+    fn create_score_txt(score: u32, strings: &Strings, font: Font) -> Text {
+        let digits = maths::digit_count(score) as f32;
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.score_label, &[&score]))
+                .scale(graphics::Scale::uniform(24. * (5. / f32::max(5., digits))))
+                .font(font),
+        )
+    }
+    fn create_combo_txt(multiplier: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.combo_label, &[&multiplier]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_level_txt(id: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.level_label, &[&id]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_level_clear_txt(id: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.level_clear, &[&id]))
+                .scale(graphics::Scale::uniform(48.))
+                .font(font),
+        )
+    }
+    fn create_score2_txt(score: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.player_two_score_label, &[&score]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_two_player_txt(
+        two_player: bool,
+        strings: &Strings,
+        keys: &KeyBindings,
+        font: Font,
+    ) -> Text {
+        let state = strings.on_off(two_player);
+        let key = input::key_name(&keys.two_player);
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.two_player, &[&state, &key]))
+                .scale(graphics::Scale::uniform(32.))
+                .font(font),
+        )
+    }
+    fn create_two_player_final_score_txt(
+        score: u32,
+        score2: u32,
+        strings: &Strings,
+        font: Font,
+    ) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(
+                &strings.two_player_final_score,
+                &[&score, &score2],
+            ))
+            .scale(graphics::Scale::uniform(32.))
+            .font(font),
+        )
+    }
+    fn create_highscore_txt(score: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.highscore_label, &[&score]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_best_time_txt(best: Option<u64>, strings: &Strings, font: Font) -> Text {
+        let time = best.map_or_else(|| "--:--.---".to_owned(), speedrun::format_time);
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.highscore_label, &[&time]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_difficulty_txt(difficulty: DifficultyLevel, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.difficulty_label, &[&difficulty]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_pregame_difficulty_txt(
+        difficulty: DifficultyLevel,
+        strings: &Strings,
+        keys: &KeyBindings,
+        font: Font,
+    ) -> Text {
+        let key = input::key_name(&keys.difficulty);
+        Text::new(
+            TextFragment::new(i18n::fill(
+                &strings.pregame_difficulty,
+                &[&difficulty, &key],
+            ))
+            .scale(graphics::Scale::uniform(32.))
+            .font(font),
+        )
+    }
+    fn create_wrap_walls_txt(
+        wrap_walls: bool,
+        strings: &Strings,
+        keys: &KeyBindings,
+        font: Font,
+    ) -> Text {
+        let state = strings.on_off(wrap_walls);
+        let key = input::key_name(&keys.wrap_walls);
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.wrap_walls, &[&state, &key]))
+                .scale(graphics::Scale::uniform(32.))
+                .font(font),
+        )
+    }
+    fn create_record_txt(record: bool, strings: &Strings, keys: &KeyBindings, font: Font) -> Text {
+        let state = strings.on_off(record);
+        let (record_key, replay_key) =
+            (input::key_name(&keys.record), input::key_name(&keys.replay));
+        Text::new(
+            TextFragment::new(i18n::fill(
+                &strings.record,
+                &[&state, &record_key, &replay_key],
+            ))
+            .scale(graphics::Scale::uniform(32.))
+            .font(font),
+        )
+    }
+    fn create_final_score_txt(score: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.final_score, &[&score]))
+                .scale(graphics::Scale::uniform(32.))
+                .font(font),
+        )
+    }
+    fn create_stats_txt(stats: &SessionStats, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(
+                &strings.stats,
+                &[
+                    &stats.games_played,
+                    &format!("{:.0}", stats.average_score()),
+                    &stats.best_score,
+                    &format!("{:.0}", stats.max_length_ever),
+                ],
+            ))
+            .scale(graphics::Scale::uniform(24.))
+            .font(font),
+        )
+    }
+    fn create_survival_time_txt(secs: u32, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.survival_time, &[&secs]))
+                .scale(graphics::Scale::uniform(32.))
+                .font(font),
+        )
+    }
+    fn create_autoplay_txt(strings: &Strings, keys: &KeyBindings, font: Font) -> Text {
+        let key = input::key_name(&keys.autoplay);
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.autoplay, &[&strings.on, &key]))
+                .scale(graphics::Scale::uniform(32.))
+                .font(font),
+        )
+    }
+    fn create_pregame_txt(strings: &Strings, keys: &KeyBindings, font: Font) -> Text {
+        let key = input::key_name(&keys.start);
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.press_to_start, &[&key]))
+                .scale(graphics::Scale::uniform(64.))
+                .font(font),
+        )
+    }
+    fn create_paused_txt(strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(strings.paused.as_str())
+                .scale(graphics::Scale::uniform(64.))
+                .font(font),
+        )
+    }
+    fn create_new_record_txt(strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(strings.new_record.as_str())
+                .scale(graphics::Scale::uniform(48.))
+                .font(font),
+        )
+    }
+    fn create_death_txt(cause: Option<&DeathCause>, strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(cause.map_or_else(String::new, |cause| strings.death_cause(cause)))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_daily_txt((year, month, day): daily::Date, strings: &Strings, font: Font) -> Text {
+        let date = format!(DATE_FMT!(), year, month, day);
+        Text::new(
+            TextFragment::new(i18n::fill(&strings.daily_label, &[&date]))
+                .scale(graphics::Scale::uniform(24.))
+                .font(font),
+        )
+    }
+    fn create_game_over_txt(strings: &Strings, font: Font) -> Text {
+        Text::new(
+            TextFragment::new(strings.game_over.as_str())
+                .scale(graphics::Scale::uniform(64.))
+                .font(font),
+        )
+    }
+
+    /// Draws the background and the terrain.
+    ///
+    /// With `GameConfig::cache_static_layer` they are drawn into `static_layer` only
+    /// when it was cleared, after that the canvas alone is drawn.
+    ///
+    pub fn draw_static_layer(&mut self, ctx: &mut Context) -> GameResult {
+        if !self.config.cache_static_layer {
+            Renderer::draw_bg(ctx, &self.resources.bg_image, &self.theme);
+            self.board.draw(ctx);
+            return Ok(());
+        }
+
+        if self.static_layer.is_none() {
+            let canvas = Canvas::new(
+                ctx,
+                self.screen.x as u16,
+                self.screen.y as u16,
+                conf::NumSamples::One,
+            )?;
+            // screen shake must not be baked into the canvas
+            graphics::push_transform(ctx, Some(graphics::DrawParam::new().to_matrix()));
+            graphics::apply_transformations(ctx)?;
+            graphics::set_canvas(ctx, Some(&canvas));
+            graphics::clear(ctx, graphics::BLACK);
+            Renderer::draw_bg(ctx, &self.resources.bg_image, &self.theme);
+            self.board.draw(ctx);
+            graphics::set_canvas(ctx, None);
+            graphics::pop_transform(ctx);
+            graphics::apply_transformations(ctx)?;
+            self.static_layer = Some(canvas);
+        }
+        if let Some(canvas) = &self.static_layer {
+            graphics::draw(ctx, canvas, graphics::DrawParam::default())?;
+        }
+        Ok(())
+    }
+
+    /// Draws everything in the game world, ordered by the `z_order` of each element.
+    /// Elements in the same layer are drawn in the order they are listed here.
+    ///
+    pub fn draw_world(&self, ctx: &mut Context) {
+        let alpha = self.alpha();
+        let rainbow_hue = self.rainbow_hue();
+        let mut obstacle_color = self.theme.obstacle_color;
+        obstacle_color.a *= 1. - self.level_clear_progress();
+
+        let mut layers: Vec<DrawLayer<'_>> = Vec::new();
+        if self.state != GameState::Respawning {
+            layers.push((
+                self.snake.z_order(),
+                Box::new(move |ctx| {
+                    Renderer::draw_snake(ctx, &self.snake, &self.theme, alpha, rainbow_hue);
+                    if self.power_ups.is_shielded() {
+                        let head = self.snake.body.front().unwrap().tip(alpha);
+                        Renderer::draw_shield(ctx, head, FoodKind::Shield.color());
+                    }
+                }),
+            ));
+        }
+        if let Some(snake2) = &self.snake2 {
+            layers.push((
+                snake2.z_order(),
+                Box::new(move |ctx| {
+                    Renderer::draw_snake(ctx, snake2, &self.theme2, alpha, rainbow_hue)
+                }),
+            ));
+        }
+        for obstacle in &self.obstacles {
+            layers.push((
+                obstacle.z_order(),
+                Box::new(move |ctx| {
+                    Renderer::draw_with_glow(ctx, obstacle, 1., obstacle_color, &self.theme)
+                }),
+            ));
+        }
+        layers.push((
+            consts::Z_WORLD,
+            Box::new(move |ctx| {
+                Renderer::draw_hazard_zone(ctx, &self.shrink_border.hazard_rects(self.screen))
+            }),
+        ));
+        for portal in &self.portals {
+            layers.push((portal.z_order(), Box::new(move |ctx| portal.draw(ctx))));
+        }
+        if let Some(idx) = self.magnet_target() {
+            let (head, food) = (self.snake.head_position(), self.foods[idx].center());
+            layers.push((
+                consts::Z_WORLD,
+                Box::new(move |ctx| Renderer::draw_magnet_pull(ctx, head, food)),
+            ));
+        }
+        for food in &self.foods {
+            layers.push((
+                food.z_order(),
+                Box::new(move |ctx| {
+                    Renderer::draw_food(ctx, food, &self.resources.food_image, &self.theme);
+                    if self.power_ups.is_frozen() {
+                        Renderer::draw_frost(ctx, food.bbox);
+                    }
+                }),
+            ));
+        }
+        layers.push((
+            self.particles.z_order(),
+            Box::new(move |ctx| self.particles.draw(ctx)),
+        ));
+        for popup in &self.popups {
+            layers.push((
+                popup.z_order(),
+                Box::new(move |ctx| popup.draw(ctx, self.resources.font)),
+            ));
+        }
+
+        // sorting is stable, so the listed order is kept within a layer
+        layers.sort_by_key(|&(z_order, _)| z_order);
+        for (_, draw) in layers {
+            draw(ctx);
+        }
+
+        if self.power_ups.is_frozen() {
+            Renderer::draw_flash(ctx, self.screen, graphics::Color::new(0.4, 0.7, 1., 0.15));
+        }
+    }
+
+    /// Draws the score, the snake length, the combo multiplier, the best score
+    /// and a flashing new record notification, when one was just set.
+    ///
+    pub fn draw_hud(&self, ctx: &mut Context) {
+        let score_pos = Coords::new(10., 10.);
+        Renderer::draw_text_with_outline(ctx, &self.score_txt, score_pos, &self.theme);
+
+        let (score_width, score_height) = self.score_txt.dimensions(ctx);
+        let (score_width, score_height) = (score_width as f32, score_height as f32);
+        let length_txt = Text::new(
+            TextFragment::new(i18n::fill(
+                &self.strings.length_label,
+                &[&format!("{:.1}", self.snake.length())],
+            ))
+            .scale(graphics::Scale::uniform(24.))
+            .font(self.resources.font),
+        );
+        let length_pos = Coords::new(score_pos.x, score_pos.y + score_height + 6.);
+        Renderer::draw_text_with_outline(ctx, &length_txt, length_pos, &self.theme);
+
+        // shows only when it actually multiplies, flashes on long combos
+        let combo_flash = self.combo < consts::COMBO_FLASH_MIN
+            || ((self.combo_timer * 4.) as u32).is_multiple_of(2);
+        if self.combo_multiplier() > 1 && combo_flash {
+            let length_height = length_txt.height(ctx) as f32;
+            Renderer::draw_text_with_outline(
+                ctx,
+                &self.combo_txt,
+                Coords::new(length_pos.x, length_pos.y + length_height + 6.),
+                &self.theme,
+            );
+        }
+
+        Renderer::draw_text_with_outline(
+            ctx,
+            &self.highscore_txt,
+            Coords::new(score_pos.x + score_width + 30., score_pos.y),
+            &self.theme,
+        );
+
+        let difficulty_width = self.difficulty_txt.width(ctx) as f32;
+        Renderer::draw_text_with_outline(
+            ctx,
+            &self.difficulty_txt,
+            Coords::new(self.screen.x - difficulty_width - score_pos.x, score_pos.y),
+            &self.theme,
+        );
+
+        if self.two_player {
+            let score2_height = self.score2_txt.height(ctx) as f32;
+            Renderer::draw_text_with_outline(
+                ctx,
+                &self.score2_txt,
+                Coords::new(score_pos.x, self.screen.y - score2_height - score_pos.y),
+                &self.theme,
+            );
+        }
+
+        let level_width = self.level_txt.width(ctx) as f32;
+        let level_height = self.level_txt.height(ctx) as f32;
+        Renderer::draw_text_with_outline(
+            ctx,
+            &self.level_txt,
+            Coords::new(
+                self.screen.x - level_width - score_pos.x,
+                self.screen.y - level_height - score_pos.y,
+            ),
+            &self.theme,
+        );
+
+        if self.hunger_rate.is_finite() {
+            self.draw_hunger_bar(ctx);
+        }
+        if self.mode.time_limit().is_some() {
+            self.draw_remaining_time(ctx, score_pos.y);
+        }
+        if self.mode == GameMode::Survival {
+            self.draw_speed_percent(ctx, score_pos.y);
+        }
+        if let Some(timer) = &self.speedrun_timer {
+            self.draw_speedrun_timer(ctx, timer.elapsed_ms(), score_pos.y);
+        }
+        if let Some(secs) = self.power_ups.confusion {
+            self.draw_confusion_icon(ctx, secs, score_pos.y + 40.);
+        }
+        if let Some(daily_txt) = &self.daily_txt {
+            let width = daily_txt.width(ctx) as f32;
+            Renderer::draw_text_with_outline(
+                ctx,
+                daily_txt,
+                Coords::new(self.screen.x / 2. - width / 2., score_pos.y),
+                &self.theme,
+            );
+        }
+
+        // blink with 4 Hz while the timer is running
+        if self.new_record_timer > 0. && ((self.new_record_timer * 4.) as u32).is_multiple_of(2) {
+            Renderer::draw_centered_text_with_outline(
+                ctx,
+                &self.new_record_txt,
+                -self.screen.y / 3.,
+                &self.theme,
+            );
+        }
+
+        if self.mode != GameMode::AutoPlay && self.player.is_none() {
+            let width = self.config.max_input_queue as f32 * consts::INPUT_SQUARE_SIZE * 2.;
+            Renderer::draw_input_buffer(
+                ctx,
+                Coords::new(
+                    (self.screen.x - width) / 2.,
+                    self.screen.y - consts::INPUT_SQUARE_SIZE * 2.,
+                ),
+                self.inputs.len(),
+                self.config.max_input_queue,
+                &self.theme,
+            );
+        }
+
+        if let Some(color) = self.danger_level().border_color(self.run_time) {
+            Renderer::draw_danger_border(ctx, self.screen, color);
+        }
+
+        if self.config.lives > 1 {
+            let hearts_y = score_pos.y + self.difficulty_txt.height(ctx) as f32 + 6.;
+            let hearts_width = self.config.lives as f32 * consts::HEART_SIZE * 1.5;
+            Renderer::draw_hearts(
+                ctx,
+                Coords::new(self.screen.x - hearts_width - score_pos.x, hearts_y),
+                self.lives,
+                self.config.lives,
+                &self.theme,
+            );
+        }
+    }
+
+    /// Returns how close the first snake is to crashing into a wall or into itself.
+    /// Ghost snake is always safe.
+    ///
+    pub fn danger_level(&self) -> DangerLevel {
+        if self.power_ups.is_ghost() {
+            return DangerLevel::Safe;
+        }
+
+        let margin = self.shrink_border.margin;
+        let walls = !self.wrap_walls || margin > 0.;
+        DangerLevel::of(&self.snake, self.screen, walls, margin)
+    }
+
+    /// Hands every event queued during the last physics step over to all listeners:
+    /// particles and sound effects.
+    ///
+    /// Events are also checked for new achievements, which are shown as a notice.
+    /// Replays and auto-play runs do not unlock any.
+    ///
+    fn dispatch_events(&mut self) {
+        let counts = self.player.is_none() && self.mode != GameMode::AutoPlay;
+        let mut unlocked = Vec::new();
+        for event in self.event_queue.drain(..) {
+            let listeners: [&mut dyn GameEventListener; 2] =
+                [&mut self.particles, &mut self.resources];
+            for listener in listeners {
+                listener.on_event(&event);
+            }
+            if counts {
+                unlocked.extend(self.achievements.check(&event, &self.stats));
+            }
+        }
+        if unlocked.is_empty() {
+            return;
+        }
+
+        self.achievements.save();
+        let names = unlocked
+            .iter()
+            .map(|&achievement| self.strings.achievement_name(achievement))
+            .join(", ");
+        let text = i18n::fill(&self.strings.achievement_unlocked, &[&names]);
+        self.notice = Some(ScorePopup::notice(self.screen / 2., &text));
+    }
+
+    /// Advances the game by the real time elapsed since the last frame,
+    /// in steps of `consts::SECS_PER_PHYSICS_STEP`.
+    ///
+    /// Time that does not fill a whole step is kept for the next frame,
+    /// so the game runs with the same speed and gives the same results on every display.
+    /// Frames longer than `consts::MAX_FRAME_SECS` are cut down to it,
+    /// so a stalled window does not have to catch up with many steps at once.
+    ///
+    pub fn step(&mut self, time_delta: f32) {
+        self.accumulator += f32::min(time_delta, consts::MAX_FRAME_SECS);
+        while self.accumulator >= consts::SECS_PER_PHYSICS_STEP && self.state == GameState::Game {
+            self.accumulator -= consts::SECS_PER_PHYSICS_STEP;
+            self.run_time += consts::SECS_PER_PHYSICS_STEP;
+            self.update_input(consts::SECS_PER_PHYSICS_STEP);
+            self.update_snake(consts::SECS_PER_PHYSICS_STEP);
+            self.dispatch_events();
+            self.max_length = f32::max(self.max_length, self.snake.length());
+        }
+    }
+
+    /// Returns how far the game is between the last and the next physics step, between 0 and 1
+    ///
+    pub fn alpha(&self) -> f32 {
+        f32::clamp(self.accumulator / consts::SECS_PER_PHYSICS_STEP, 0., 1.)
+    }
+
+    /// Draws the elapsed time of a speed run, given in milliseconds,
+    /// centered at the height `y`.
+    ///
+    fn draw_speedrun_timer(&self, ctx: &mut Context, time_ms: u64, y: f32) {
+        let txt = Text::new(
+            TextFragment::new(speedrun::format_time(time_ms))
+                .scale(graphics::Scale::uniform(24.))
+                .font(self.resources.font),
+        );
+        let width = txt.width(ctx) as f32;
+        Renderer::draw_text_with_outline(
+            ctx,
+            &txt,
+            Coords::new(self.screen.x / 2. - width / 2., y),
+            &self.theme,
+        );
+    }
+
+    /// Draws the countdown of the timed mode at the top of the screen,
+    /// in red when the time is nearly up.
+    ///
+    fn draw_remaining_time(&self, ctx: &mut Context, y: f32) {
+        let txt = Text::new(
+            TextFragment::new(i18n::fill(
+                &self.strings.remaining_time_label,
+                &[&format!("{:.1}", self.remaining_time)],
+            ))
+            .scale(graphics::Scale::uniform(24.))
+            .font(self.resources.font),
+        );
+        let color = if self.remaining_time < consts::TIMED_WARNING_SECS {
+            graphics::Color::from_rgb(255, 60, 60)
+        } else {
+            self.theme.text_color
+        };
+        let width = txt.width(ctx) as f32;
+        Renderer::draw_colored_text_with_outline(
+            ctx,
+            &txt,
+            Coords::new(self.screen.x / 2. - width / 2., y),
+            color,
+        );
+    }
+
+    /// Draws a bar at the bottom of the screen, that depletes
+    /// until the snake gets hungry and shrinks.
+    ///
+    fn draw_hunger_bar(&self, ctx: &mut Context) {
+        let size = consts::HUNGER_BAR_SIZE;
+        let frame = graphics::Rect::new(
+            self.screen.x / 2. - size.x / 2.,
+            self.screen.y - size.y - 20.,
+            size.x,
+            size.y,
+        );
+        let mut fill = frame;
+        fill.w *= f32::clamp(self.hunger_timer / self.hunger_rate, 0., 1.);
+
+        let mesh = graphics::MeshBuilder::new()
+            .rectangle(
+                graphics::DrawMode::fill(),
+                fill,
+                graphics::Color::from_rgb(255, 160, 40),
+            )
+            .rectangle(graphics::DrawMode::stroke(2.), frame, graphics::BLACK)
+            .build(ctx)
+            .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing hunger bar");
+    }
+
+    /// Draws a purple exclamation mark at the top of the screen,
+    /// flashing with 4 Hz while the controls are reversed
+    ///
+    fn draw_confusion_icon(&self, ctx: &mut Context, secs_left: f32, y: f32) {
+        if !((secs_left * 4.) as u32).is_multiple_of(2) {
+            return;
+        }
+        let txt = Text::new(
+            TextFragment::new(consts::CONFUSION_TXT)
+                .scale(graphics::Scale::uniform(40.))
+                .font(self.resources.font),
+        );
+        let width = txt.width(ctx) as f32;
+        Renderer::draw_colored_text_with_outline(
+            ctx,
+            &txt,
+            Coords::new(self.screen.x / 2. - width / 2., y),
+            FoodKind::Confusion.color(),
+        );
+    }
+
+    /// Draws the current survival speed at the top of the screen, relative to the normal one
+    ///
+    fn draw_speed_percent(&self, ctx: &mut Context, y: f32) {
+        let txt = Text::new(
+            TextFragment::new(i18n::fill(
+                &self.strings.speed_label,
+                &[&format!("{:.0}", self.survival.speed_multiplier() * 100.)],
+            ))
+            .scale(graphics::Scale::uniform(24.))
+            .font(self.resources.font),
+        );
+        let width = txt.width(ctx) as f32;
+        Renderer::draw_text_with_outline(
+            ctx,
+            &txt,
+            Coords::new(self.screen.x / 2. - width / 2., y),
+            &self.theme,
+        );
+    }
+
+    /// Performs the action requested in the main menu or in the settings.
+    ///
+    /// Play leads to the pre-game screen, Quit closes the game.
+    /// Settings are applied as soon as they change, going back
+    /// from the settings saves them and returns to the main menu.
+    /// The colors in use can be exported from the settings with `export_palette`.
+    ///
+    pub fn handle_menu_action(&mut self, ctx: &mut Context, action: MenuAction) {
+        match (self.state, action) {
+            (GameState::Menu, MenuAction::Up) => self.menu.select_prev(),
+            (GameState::Menu, MenuAction::Down) => self.menu.select_next(),
+            (GameState::Menu, MenuAction::Select) => match self.menu.selected_item() {
+                MenuItem::Play => self.state = GameState::PreGame,
+                MenuItem::Settings => self.state = GameState::Settings,
+                MenuItem::Quit => event::quit(ctx),
+            },
+            (GameState::Settings, MenuAction::Up) => self.settings.select_prev(),
+            (GameState::Settings, MenuAction::Down) => self.settings.select_next(),
+            (GameState::Settings, MenuAction::Left) => self.change_setting(-1),
+            (GameState::Settings, MenuAction::Right) => self.change_setting(1),
+            (GameState::Settings, MenuAction::Back) => {
+                if let Err(err) = self.config.save(&self.config_path) {
+                    eprintln!("{}", err);
+                }
+                self.state = GameState::Menu;
+            }
+            (GameState::Settings, MenuAction::ExportPalette) => self.export_palette(),
+            _ => {}
+        }
+    }
+
+    /// Writes the colors in use, from the current palette or the config theme,
+    /// into `consts::PALETTE_EXPORT_FILE` next to the config file
+    /// and shows a notice about the outcome.
+    ///
+    fn export_palette(&mut self) {
+        let palette = match self.current_palette {
+            Some(idx) => self.palettes[idx].clone(),
+            None => ColorPalette::from_theme(&self.config.theme.to_string(), &self.theme),
+        };
+        let path = self.config_path.with_file_name(consts::PALETTE_EXPORT_FILE);
+        let text = match palette.save(&path) {
+            Ok(()) => i18n::fill(&self.strings.palette_exported, &[&path.display()]),
+            Err(err) => {
+                eprintln!("{}", err);
+                self.strings.palette_export_failed.clone()
+            }
+        };
+        self.notice = Some(ScorePopup::notice(self.screen / 2., &text));
+    }
+
+    /// Switches to the next palette from `consts::PALETTES_DIR`, applied on top of the theme
+    /// from the config. After the last palette the config theme is used as it is.
+    ///
+    pub fn cycle_palette(&mut self) {
+        if self.palettes.is_empty() {
+            return;
+        }
+
+        self.current_palette = match self.current_palette {
+            None => Some(0),
+            Some(idx) if idx + 1 < self.palettes.len() => Some(idx + 1),
+            Some(_) => None,
+        };
+        let base = ColorTheme::from_name(self.config.theme);
+        let (theme, name) = match self.current_palette {
+            Some(idx) => (
+                self.palettes[idx].to_theme(&base),
+                self.palettes[idx].name.clone(),
+            ),
+            None => (base, self.config.theme.to_string()),
+        };
+        self.theme = theme;
+        self.theme2 = theme.player_two();
+        self.static_layer = None;
+        self.notice = Some(ScorePopup::notice(self.screen / 2., &name));
+    }
+
+    /// Changes the selected setting by `steps` and applies the new value right away
+    ///
+    fn change_setting(&mut self, steps: i32) {
+        self.settings
+            .selected_item()
+            .change(&mut self.config, steps);
+        self.theme = ColorTheme::from_name(self.config.theme);
+        self.theme2 = self.theme.player_two();
+        self.current_palette = None;
+        self.static_layer = None;
+        self.apply_sfx_volume();
+        self.key_bindings = KeyBindings::from_config(&self.config);
+        self.rebuild_key_hints();
+        if self.difficulty != self.config.difficulty {
+            self.set_difficulty(self.config.difficulty);
+        }
+    }
+
+    /// Switches between `GameState::Game` and `GameState::Paused`.
+    ///
+    /// On resume `delta_time` is reset, so the time spent in pause
+    /// is not fed into the next update.
+    /// On the pre-game screen it goes back to the main menu.
+    ///
+    pub fn toggle_pause(&mut self) {
+        match self.state {
+            GameState::Game => {
+                self.state = GameState::Paused;
+                if let Some(timer) = &mut self.speedrun_timer {
+                    timer.pause();
+                }
+                self.autosave();
+            }
+            GameState::Paused => {
+                if let Some(timer) = &mut self.speedrun_timer {
+                    timer.resume();
+                }
+                self.delta_time = Instant::now();
+                self.state = GameState::Game;
+            }
+            GameState::PreGame => self.state = GameState::Menu,
+            GameState::Menu | GameState::Settings | GameState::Respawning | GameState::GameOver => {
+            }
+        }
+    }
+
+    /// Returns a snapshot of the current run, encoded with bincode.
+    /// It can be resumed with `deserialize_state`.
+    ///
+    pub fn serialize_state(&self) -> Vec<u8> {
+        let save = SaveState {
+            mode: self.mode,
+            difficulty: self.difficulty,
+            wrap_walls: self.wrap_walls,
+            two_player: self.two_player,
+            seed: self.seed,
+            current_level: self.current_level,
+            snake: (&self.snake).into(),
+            snake2: self.snake2.as_ref().map(Into::into),
+            foods: self.foods.clone(),
+            score: self.score,
+            score2: self.score2,
+            combo: self.combo,
+            combo_timer: self.combo_timer,
+            inputs: self.inputs.clone(),
+            inputs2: self.inputs2.clone(),
+            input_timer: self.input_timer,
+            input_timer2: self.input_timer2,
+            power_ups: self.power_ups,
+            hunger_timer: self.hunger_timer,
+            lives: self.lives,
+            remaining_time: self.remaining_time,
+            survival: self.survival,
+            shrink_border: self.shrink_border,
+            run_time: self.run_time,
+            max_length: self.max_length,
+            total_moves: self.total_moves,
+            total_turns: self.total_turns,
+        };
+        bincode::serialize(&save).expect("Error while serializing save")
+    }
+
+    /// Replaces the current run with the one from `data`, made by `serialize_state`,
+    /// and leaves it paused.
+    ///
+    /// The level is loaded once again, so obstacles and portals are placed like in the saved run.
+    ///
+    pub fn deserialize_state(&mut self, data: &[u8]) -> GameResult {
+        let save: SaveState = bincode::deserialize(data)
+            .map_err(|err| GameError::FilesystemError(format!("Malformed save: {}", err)))?;
+        self.mode = save.mode;
+        self.two_player = save.two_player;
+        self.wrap_walls = save.wrap_walls;
+        self.set_difficulty(save.difficulty);
+        self.restart(save.seed);
+        if save.current_level != self.current_level {
+            self.current_level = save.current_level.min(self.levels.len() as u32 - 1);
+            self.load_current_level();
+        }
+        self.snake = save.snake.into();
+        self.snake2 = save.snake2.map(Into::into);
+        self.foods = save.foods;
+        self.score = save.score;
+        self.score2 = save.score2;
+        self.combo = save.combo;
+        self.combo_timer = save.combo_timer;
+        self.inputs = save.inputs;
+        self.inputs2 = save.inputs2;
+        self.input_timer = save.input_timer;
+        self.input_timer2 = save.input_timer2;
+        self.power_ups = save.power_ups;
+        self.hunger_timer = save.hunger_timer;
+        self.lives = save.lives;
+        self.remaining_time = save.remaining_time;
+        self.survival = save.survival;
+        self.shrink_border = save.shrink_border;
+        self.run_time = save.run_time;
+        self.max_length = save.max_length;
+        self.total_moves = save.total_moves;
+        self.total_turns = save.total_turns;
+        self.static_layer = None;
+        self.rebuild_texts();
+        self.state = GameState::Paused;
+
+        Ok(())
+    }
+
+    /// Creates once again every text that shows a part of the run state,
+    /// as texts are not stored in the save.
+    ///
+    pub fn rebuild_texts(&mut self) {
+        let font = self.resources.font;
+        self.score_txt = Self::create_score_txt(self.score, &self.strings, font);
+        self.score2_txt = Self::create_score2_txt(self.score2, &self.strings, font);
+        self.combo_txt = Self::create_combo_txt(self.combo_multiplier(), &self.strings, font);
+        self.level_txt = Self::create_level_txt(self.current_level + 1, &self.strings, font);
+        self.difficulty_txt = Self::create_difficulty_txt(self.difficulty, &self.strings, font);
+        self.pregame_difficulty_txt = Self::create_pregame_difficulty_txt(
+            self.difficulty,
+            &self.strings,
+            &self.key_bindings,
+            font,
+        );
+        self.wrap_walls_txt =
+            Self::create_wrap_walls_txt(self.wrap_walls, &self.strings, &self.key_bindings, font);
+        self.two_player_txt =
+            Self::create_two_player_txt(self.two_player, &self.strings, &self.key_bindings, font);
+    }
+
+    /// Creates once again the texts that name the keys, after `key_bindings` have changed
+    ///
+    fn rebuild_key_hints(&mut self) {
+        let font = self.resources.font;
+        let (strings, keys) = (&self.strings, &self.key_bindings);
+        self.pregame_txt = Self::create_pregame_txt(strings, keys, font);
+        self.pregame_difficulty_txt =
+            Self::create_pregame_difficulty_txt(self.difficulty, strings, keys, font);
+        self.wrap_walls_txt = Self::create_wrap_walls_txt(self.wrap_walls, strings, keys, font);
+        self.record_txt = Self::create_record_txt(self.record_next, strings, keys, font);
+        self.autoplay_txt = Self::create_autoplay_txt(strings, keys, font);
+        self.two_player_txt = Self::create_two_player_txt(self.two_player, strings, keys, font);
+        self.settings.back_txt = SettingsMenu::create_back_txt(strings, keys, font);
+        self.settings.export_txt = SettingsMenu::create_export_txt(strings, keys, font);
+    }
+
+    /// Writes the current run into `consts::SAVE_FILE`, unless it is a replay
+    /// or played in a mode that cannot be saved.
+    /// Errors are only reported, as the game can go on without the save.
+    ///
+    fn autosave(&self) {
+        if self.player.is_some() || !self.mode.can_save() {
+            return;
+        }
+
+        if let Some(path) = SaveState::path() {
+            if let Err(err) = SaveState::write(&path, &self.serialize_state()) {
+                eprintln!("{}", err);
+            }
+        }
+    }
+
+    /// Resumes the run from `consts::SAVE_FILE`, when it was saved in the same mode.
+    /// Missing or malformed save leaves the fresh run untouched.
+    ///
+    fn load_autosave(&mut self) {
+        if !self.mode.can_save() {
+            return;
+        }
+        let data = match SaveState::path().and_then(|path| SaveState::read(&path).ok()) {
+            Some(data) => data,
+            None => return,
+        };
+        let same_mode = bincode::deserialize::<SaveState>(&data)
+            .map(|save| save.mode == self.mode)
+            .unwrap_or(false);
+        if same_mode {
+            if let Err(err) = self.deserialize_state(&data) {
+                eprintln!("{}", err);
+            }
+        }
+    }
+
+    /// Deletes `consts::SAVE_FILE`, once the saved run can no longer be resumed
+    ///
+    fn remove_autosave(&self) {
+        if self.player.is_some() {
+            return;
+        }
+
+        if let Some(path) = SaveState::path().filter(|path| path.exists()) {
+            if let Err(err) = std::fs::remove_file(&path) {
+                eprintln!("Could not remove {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Performs the action requested by the player.
+    ///
+    pub fn handle_action(&mut self, action: GameAction) {
+        match action {
+            // confusion swaps the directions of the player, but not of the AI
+            GameAction::Move(dir) if self.power_ups.is_confused() => {
+                self.push_input(dir.opposite())
+            }
+            GameAction::Move(dir) => self.push_input(dir),
+            GameAction::MovePlayerTwo(dir) => {
+                if self.inputs2.back() != Some(&dir)
+                    && self.inputs2.len() < self.config.max_input_queue
+                {
+                    self.inputs2.push_front(dir);
+                }
+            }
+            GameAction::Start => {
+                self.start_game();
+                self.inputs.clear();
+            }
+            GameAction::Pause => self.toggle_pause(),
+            GameAction::Reset => match self.state {
+                GameState::GameOver => self.confirm_game_over(),
+                GameState::Game | GameState::Paused | GameState::Respawning => self.reset(),
+                GameState::Menu | GameState::Settings | GameState::PreGame => {}
+            },
+            GameAction::Restart => self.restart_speedrun(),
+            GameAction::CycleDifficulty => self.set_difficulty(self.difficulty.next()),
+            GameAction::ToggleWrapWalls => self.toggle_wrap_walls(),
+            GameAction::ToggleRecording => self.toggle_recording(),
+            GameAction::PlayReplay => self.play_last_replay(),
+            GameAction::ToggleAutoPlay => self.toggle_autoplay(),
+            GameAction::ToggleTwoPlayer => self.toggle_two_player(),
+        }
+    }
+
+    /// Starts the speed run over right away, skipping the pre-game screen.
+    /// Other modes ignore it.
+    ///
+    pub fn restart_speedrun(&mut self) {
+        let running = matches!(
+            self.state,
+            GameState::Game | GameState::Paused | GameState::Respawning
+        );
+        if running && matches!(self.mode, GameMode::SpeedRun { .. }) {
+            self.reset();
+            self.start_game();
+        }
+    }
+
+    /// Queues a direction coming from any input source,
+    /// skipping it when it repeats the last queued one.
+    ///
+    /// Once `GameConfig::max_input_queue` directions wait in the queue, new ones are dropped,
+    /// so a burst of key presses does not play out long after it was made.
+    ///
+    pub fn push_input(&mut self, dir: Direction) {
+        if self.inputs.len() >= self.config.max_input_queue {
+            return;
+        }
+        if self.inputs.is_empty() || self.inputs.back().unwrap() != &dir {
+            self.inputs.push_front(dir);
+        }
+    }
+
+    /// Processes user input, capped to `GameConfig::secs_per_input_update`
+    /// scaled by the difficulty speed multiplier.
+    ///
+    /// The cap is there to make sure that 180 turns always makes enough
+    /// space between both parts of the snake.
+    ///
+    /// During playback, recorded inputs are applied instead of the player ones.
+    /// In `GameMode::AutoPlay`, directions come from the `AiController`.
+    ///
+    /// On ice, turns are applied `consts::ICE_SLIDE_STEPS` steps later.
+    ///
+    pub fn update_input(&mut self, time_delta: f32) {
+        if self.state == GameState::Paused {
+            return;
+        }
+
+        if let Some(player) = &mut self.player {
+            while let Some(dir) = player.poll(self.run_time) {
+                if self.snake.dir != dir {
+                    self.total_turns += 1;
+                }
+                self.snake.dir = dir;
+            }
+            self.inputs.clear();
+            return;
+        }
+
+        if let Some(snake2) = &mut self.snake2 {
+            self.input_timer2 += time_delta;
+            if self.input_timer2 >= self.secs_per_input_update
+                && !slides_on_ice(&self.board, snake2, &self.inputs2, &mut self.ice_slide2)
+                && apply_queued_turn(&mut self.inputs2, snake2).is_some()
+            {
+                self.input_timer2 = 0.;
+                resourceloader::play_sound(&mut self.resources.turn_sound);
+            }
+        }
+
+        if self.mode == GameMode::AutoPlay {
+            self.inputs.clear();
+            let head = self.snake.head_position();
+            let nearest = sim::nearest_food(&self.foods, head, |_| true);
+            if let Some(idx) = nearest {
+                // portals are avoided, path finding does not know where they lead
+                let blocked: Vec<_> = self
+                    .obstacles
+                    .iter()
+                    .map(|obstacle| obstacle.bbox)
+                    .chain(self.portals.iter().map(|portal| portal.entry))
+                    .chain(self.shrink_border.hazard_rects(self.screen))
+                    .chain(self.board.areas_of(TileKind::Void))
+                    .collect();
+                let dir =
+                    self.ai
+                        .next_direction(&self.snake, &self.foods[idx], &blocked, self.screen);
+                self.push_input(dir);
+            }
+        }
+
+        self.input_timer += time_delta;
+        if self.input_timer < self.secs_per_input_update
+            || slides_on_ice(&self.board, &self.snake, &self.inputs, &mut self.ice_slide)
+        {
+            return;
+        }
+
+        if let Some(new_dir) = apply_queued_turn(&mut self.inputs, &mut self.snake) {
+            self.input_timer = 0.;
+            self.total_turns += 1;
+            resourceloader::play_sound(&mut self.resources.turn_sound);
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(self.run_time, new_dir);
+            }
+        }
+    }
+
+    /// Updates purely visual effects: particles, score popups and the screen shake
+    ///
+    pub fn update_effects(&mut self, time_delta: f32) {
+        self.particles.update(time_delta);
+        for popup in &mut self.popups {
+            popup.lifetime -= time_delta;
+        }
+        self.popups.retain(|popup| !popup.is_expired());
+        self.shake_timer = f32::max(self.shake_timer - time_delta, 0.);
+    }
+
+    /// Ages the notice shown over every screen, removing it once it faded out
+    ///
+    pub fn update_notice(&mut self, time_delta: f32) {
+        if let Some(notice) = &mut self.notice {
+            notice.lifetime -= time_delta;
+        }
+        self.notice = self.notice.take().filter(|notice| !notice.is_expired());
+    }
+
+    /// Rotates the rainbow snake hue, making a full turn every `consts::RAINBOW_ROTATION_SECS`
+    ///
+    pub fn update_hue(&mut self, time_delta: f32) {
+        self.hue_offset =
+            (self.hue_offset + time_delta * 360. / consts::RAINBOW_ROTATION_SECS) % 360.;
+    }
+
+    /// Hue offset of the rainbow snake, `None` when the snake uses theme colors
+    ///
+    fn rainbow_hue(&self) -> Option<f32> {
+        Some(self.hue_offset).filter(|_| self.config.rainbow_snake)
+    }
+
+    /// Saves the current frame as a PNG file on the desktop,
+    /// named after the current time, and shows a notice about the outcome.
+    ///
+    pub fn take_screenshot(&mut self, ctx: &mut Context) {
+        let text = match Self::save_screenshot(ctx) {
+            Ok(_) => &self.strings.screenshot_saved,
+            Err(err) => {
+                eprintln!("{}", err);
+                &self.strings.screenshot_failed
+            }
+        };
+        self.notice = Some(ScorePopup::notice(self.screen / 2., text));
+    }
+
+    fn save_screenshot(ctx: &mut Context) -> GameResult<PathBuf> {
+        let dir = dirs::desktop_dir().ok_or_else(|| {
+            GameError::FilesystemError("Could not find the desktop directory".to_string())
+        })?;
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let path = dir.join(format!(SCREENSHOT_FILE_FMT!(), secs));
+
+        let frame = graphics::screenshot(ctx)?;
+        let pixels = frame.to_rgba8(ctx)?;
+        image::save_buffer(
+            &path,
+            &pixels,
+            frame.width().into(),
+            frame.height().into(),
+            image::ColorType::RGBA(8),
+        )
+        .map_err(|err| {
+            GameError::FilesystemError(format!("Could not write {}: {}", path.display(), err))
+        })?;
+
+        Ok(path)
+    }
+
+    /// Returns a random offset of the whole screen, fading out with the shake timer
+    ///
+    pub fn shake_offset(&self) -> Coords {
+        if self.shake_timer <= 0. {
+            return Coords::default();
+        }
+
+        let mut rng = rand::thread_rng();
+        let strength = self.shake_magnitude
+            * maths::apply_easing(self.shake_timer / consts::SHAKE_SECS, maths::ease_in_quad);
+        Coords::new(rng.gen::<f32>() - 0.5, rng.gen::<f32>() - 0.5) * strength
+    }
+
+    /// Returns the speed the snake moves with right now,
+    /// including the level, power-ups and the survival ramp.
+    ///
+    pub fn current_speed(&self) -> f32 {
+        let mut speed = self.speed * self.level_speed_mult;
+        if self.power_ups.is_speed_boosted() {
+            speed *= consts::SPEED_BOOST_MULT;
+        }
+        if self.mode == GameMode::Survival {
+            speed *= self.survival.speed_multiplier();
+        }
+        speed
+    }
+
+    /// Checks if the snake crashed into a wall or into the hazard border.
+    /// With wrapping walls only the hazard border is deadly.
+    ///
+    fn hits_wall(&self, snake: &Snake) -> bool {
+        let margin = self.shrink_border.margin;
+        if self.wrap_walls && margin <= 0. {
+            return false;
+        }
+        snake.wall_collide(self.screen, margin)
+    }
+
+    /// Returns why the snake dies in its current position, if it does.
+    /// `hash`, when given, has to hold the current segments of `snake`.
+    /// `other` is the snake of the other player.
+    ///
+    fn crash_cause(
+        &self,
+        snake: &Snake,
+        hash: Option<&SpatialHash>,
+        other: Option<&Snake>,
+    ) -> Option<DeathCause> {
+        sim::crash_cause(
+            snake,
+            hash,
+            self.hits_wall(snake),
+            self.board.tile_at(snake.head_position()) == TileKind::Void,
+            &self.obstacles,
+            other,
+        )
+    }
+
+    /// Narrows the safe zone of `GameMode::Shrinking` once in a while.
+    /// Food caught in the hazard border is placed once again.
+    ///
+    fn update_shrink_border(&mut self, time_delta: f32) {
+        if !self.shrink_border.tick(time_delta, self.screen) {
+            return;
+        }
+
+        let hazards = self.shrink_border.hazard_rects(self.screen);
+        self.foods
+            .retain(|food| !hazards.iter().any(|hazard| hazard.overlaps(&food.bbox)));
+        self.refill_food();
+    }
+
+    /// Applies the effect of eating a given food kind.
+    /// Shrinking leaves a spray of particles where the tail was cut off,
+    /// flying away from the receding tail.
+    /// Magnet shoves the snake towards the food it starts pulling, replacing queued turns.
+    ///
+    fn eat(&mut self, kind: FoodKind) {
+        sim::grow_from_food(&mut self.snake, kind);
+        if kind == FoodKind::Shrink {
+            self.particles.spawn_spray(
+                self.snake.tail_position(),
+                consts::EAT_PARTICLES,
+                kind.color(),
+                self.snake.tail_direction().opposite(),
+            );
+        }
+        self.power_ups.apply(kind);
+        if kind == FoodKind::Magnet {
+            if let Some(dir) = sim::magnet_impulse(&mut self.snake, &self.foods, &self.power_ups) {
+                self.inputs.clear();
+                self.push_input(dir);
+            }
+        }
+    }
+
+    /// Returns the index of the food pulled by the magnet: the eatable one nearest to the head,
+    /// or `None` when the magnet is not active
+    ///
+    fn magnet_target(&self) -> Option<usize> {
+        sim::magnet_target(&self.foods, self.snake.head_position(), &self.power_ups)
+    }
+
+    /// Updates snake (collision, movement, growth)
+    /// Upon collision with anything (self, wall, food) takes proper action.
+    ///
+    /// While ghost effect is active, collisions are ignored and walls wrap around.
+    /// Active shield blocks a single crash, pulling the head back to where it was safe.
+    ///
+    /// While magnet is active, the food nearest to the head is pulled towards it
+    /// with `consts::MAGNET_SPEED`, until the snake eats it.
+    ///
+    /// While freeze is active, the time limit, hunger, food aging, portals and the shrinking border
+    /// stand still, but the snake keeps moving and turning. New food still appears and pulses.
+    ///
+    /// When the snake does not eat for `hunger_rate` seconds, it shrinks
+    /// by `GameConfig::hunger_shrink` and dies once it is down to the minimum length.
+    ///
+    pub fn update_snake(&mut self, time_delta: f32) {
+        if self.state == GameState::Paused {
+            return;
+        }
+
+        self.new_record_timer = f32::max(self.new_record_timer - time_delta, 0.);
+        let frozen = self.power_ups.is_frozen();
+        if self.mode.time_limit().is_some() && !frozen {
+            self.remaining_time = f32::max(self.remaining_time - time_delta, 0.);
+            if self.remaining_time <= 0. {
+                self.last_death = Some(DeathCause::TimeUp);
+                self.game_over();
+                return;
+            }
+        }
+        if self.mode == GameMode::Survival {
+            self.survival.elapsed += time_delta;
+            self.survival.grow_timer += time_delta;
+            if self.survival.grow_timer >= consts::SURVIVAL_GROW_SECS {
+                self.survival.grow_timer -= consts::SURVIVAL_GROW_SECS;
+                self.snake.grow(consts::SURVIVAL_GROW_LEN);
+            }
+        }
+        if self.combo > 0 {
+            self.combo_timer = f32::max(self.combo_timer - time_delta, 0.);
+            if self.combo_timer <= 0. {
+                self.set_combo(0);
+            }
+        }
+        if self.level_clear_timer > 0. {
+            self.level_clear_timer = f32::max(self.level_clear_timer - time_delta, 0.);
+            if self.level_clear_timer <= 0. {
+                self.load_current_level();
+            }
+        }
+        let hunger_dt = if frozen { 0. } else { time_delta };
+        if sim::starve(
+            &mut self.snake,
+            &mut self.hunger_timer,
+            self.hunger_rate,
+            self.config.hunger_shrink,
+            hunger_dt,
+        ) {
+            self.particles.spawn_spray(
+                self.snake.tail_position(),
+                consts::HUNGER_PARTICLES,
+                self.theme.tail_color,
+                self.snake.tail_direction().opposite(),
+            );
+            if self.snake.length() <= consts::SNAKE_MIN_LEN {
+                self.event_queue.push(GameEvent::SnakeDied {
+                    cause: DeathCause::Hunger,
+                    pos: self.snake.head_position(),
+                    color: self.theme.head_color,
+                });
+                self.particles.spawn_along(&self.snake, &self.theme);
+                self.last_death = Some(DeathCause::Hunger);
+                self.lose_life();
+                return;
+            }
+        }
+        if self.mode == GameMode::Shrinking && !frozen {
+            self.update_shrink_border(time_delta);
+        }
+        for kind in self.power_ups.tick(time_delta) {
+            self.event_queue.push(GameEvent::PowerUpExpired { kind });
+        }
+        self.update_effects(time_delta);
+        let is_ghost = self.power_ups.is_ghost();
+        self.spatial_hash.rebuild(&self.snake.get_all_bboxes());
+        let crash = if is_ghost || self.power_ups.is_in_shield_grace() {
+            None
+        } else {
+            self.crash_cause(&self.snake, Some(&self.spatial_hash), self.snake2.as_ref())
+        };
+
+        for food in &mut self.foods {
+            if frozen {
+                food.tick_frozen(time_delta);
+            } else {
+                food.tick(time_delta);
+            }
+        }
+        if !frozen {
+            for portal in &mut self.portals {
+                portal.tick(time_delta);
+            }
+        }
+        self.foods.retain(|food| !food.is_expired());
+        self.refill_food();
+        if let Some(idx) = self.magnet_target() {
+            let head = self.snake.head_position();
+            self.foods[idx].move_towards(head, consts::MAGNET_SPEED * time_delta);
+        }
+
+        if let Some(idx) = sim::eaten_food(&self.foods, &self.snake) {
+            let food = self.foods.swap_remove(idx);
+            self.hunger_timer = self.hunger_rate;
+            self.set_combo(self.combo + 1);
+            if self.mode.time_limit().is_some() {
+                self.remaining_time += consts::TIMED_FOOD_BONUS_SECS;
+            }
+            let points = self.inc_score(sim::food_points(&food, self.combo));
+            self.popups.push(ScorePopup::new(food.center(), points));
+            self.event_queue.push(GameEvent::FoodEaten {
+                pos: food.center(),
+                kind: food.kind,
+                score: points,
+            });
+            self.eat(food.kind);
+            self.refill_food();
+            self.check_level_progress();
+            self.check_speedrun_finish();
+        } else if crash.is_some() && sim::block_crash(&mut self.snake, &mut self.power_ups) {
+            self.popups.push(ScorePopup::notice(
+                self.snake.head_position(),
+                &self.strings.shield_blocked,
+            ));
+        } else if let Some(cause) = crash {
+            // crash into itself bursts where the body was hit
+            let crash = self
+                .snake
+                .self_collide_near(&self.spatial_hash)
+                .map_or_else(
+                    || self.snake.head_position(),
+                    |hit| self.snake.contact_point(&hit),
+                );
+            self.event_queue.push(GameEvent::SnakeDied {
+                cause: cause.clone(),
+                pos: crash,
+                color: self.theme.head_color,
+            });
+            self.particles.spawn_along(&self.snake, &self.theme);
+            self.last_death = Some(cause);
+            self.lose_life();
+        } else {
+            let terrain = self.board.tile_at(self.snake.head_position());
+            self.snake
+                .do_move(time_delta * self.current_speed() * terrain.speed_modifier());
+            self.total_moves += 1;
+            if self.wrap_walls || is_ghost {
+                self.snake.wrap_around(self.screen);
+            }
+            if let Some(portal) = self
+                .portals
+                .iter()
+                .find(|portal| portal.is_entered_by(&self.snake))
+            {
+                self.snake.teleport(portal.exit_pos, portal.exit_dir);
+                self.inputs.clear();
+            }
+        }
+
+        if self.state == GameState::Game {
+            self.update_second_snake(time_delta);
+        }
+        self.event_queue.push(GameEvent::StepFinished {
+            score: self.score,
+            length: self.snake.length(),
+            run_time: self.run_time,
+            is_ghost: self.power_ups.is_ghost(),
+        });
+    }
+
+    /// Updates the second player snake, when there is one.
+    ///
+    /// The second snake eats from the same food pool and scores for itself,
+    /// but power-ups and combos work only for the first player.
+    /// Crashing into the first snake ends the game, just like into a wall.
+    ///
+    fn update_second_snake(&mut self, time_delta: f32) {
+        let mut snake2 = match self.snake2.take() {
+            Some(snake2) => snake2,
+            None => return,
+        };
+
+        let eaten = sim::eaten_food(&self.foods, &snake2);
+        let crash = self.crash_cause(&snake2, None, Some(&self.snake));
+        let mut crashed = false;
+        if let Some(idx) = eaten {
+            let food = self.foods.swap_remove(idx);
+            sim::grow_from_food(&mut snake2, food.kind);
+            let points = sim::food_points(&food, 0);
+            self.score2 += points;
+            self.score2_txt =
+                Self::create_score2_txt(self.score2, &self.strings, self.resources.font);
+            self.popups.push(ScorePopup::new(food.center(), points));
+            self.event_queue.push(GameEvent::FoodEaten {
+                pos: food.center(),
+                kind: food.kind,
+                score: points,
+            });
+        } else if let Some(cause) = crash {
+            self.event_queue.push(GameEvent::SnakeDied {
+                cause: cause.clone(),
+                pos: snake2.head_position(),
+                color: self.theme2.head_color,
+            });
+            self.particles.spawn_along(&snake2, &self.theme2);
+            self.last_death = Some(cause);
+            crashed = true;
+        } else {
+            let terrain = self.board.tile_at(snake2.head_position());
+            snake2.do_move(
+                time_delta * self.speed * self.level_speed_mult * terrain.speed_modifier(),
+            );
+            if self.wrap_walls {
+                snake2.wrap_around(self.screen);
+            }
+            if let Some(portal) = self
+                .portals
+                .iter()
+                .find(|portal| portal.is_entered_by(&snake2))
+            {
+                snake2.teleport(portal.exit_pos, portal.exit_dir);
+                self.inputs2.clear();
+            }
+        }
+
+        self.snake2 = Some(snake2);
+        if eaten.is_some() {
+            self.refill_food();
+        }
+        if crashed {
+            self.game_over();
+        }
+    }
+}
+
+/// Checks if the turn of a snake that slides on ice has to wait.
+/// Each call counts one step of waiting in `slide`, until the turn can happen.
+///
+fn slides_on_ice(
+    board: &Board,
+    snake: &Snake,
+    inputs: &VecDeque<Direction>,
+    slide: &mut u32,
+) -> bool {
+    if inputs.is_empty() || board.tile_at(snake.head_position()) != TileKind::Ice {
+        *slide = 0;
+        return false;
+    }
+
+    if *slide < consts::ICE_SLIDE_STEPS {
+        *slide += 1;
+        true
+    } else {
+        *slide = 0;
+        false
     }
 }
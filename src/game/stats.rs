@@ -0,0 +1,74 @@
+//! Module with SessionStats struct, summing up all played runs
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::game::consts;
+
+/// Structure with the outcomes of all runs played so far.
+/// Moves count the physics steps the snake moved in, turns count its direction changes.
+///
+/// Stats are kept as a JSON file next to the high score file,
+/// so they carry over between sessions.
+///
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct SessionStats {
+    pub games_played: u32,
+    pub total_score: u32,
+    pub best_score: u32,
+    pub max_length_ever: f32,
+    pub total_moves: u64,
+    pub total_turns: u64,
+}
+
+impl SessionStats {
+    /// Read stats from the file. Missing or malformed file counts as no runs played.
+    ///
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write stats into the file, creating missing directories
+    ///
+    pub fn save(&self) {
+        if let Some(path) = Self::path() {
+            let content = serde_json::to_string(self).expect("Error while serializing stats");
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, content));
+            if let Err(err) = written {
+                eprintln!("Could not save stats to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Add a finished run with its final score, the greatest length the snake reached
+    /// and the number of moves and turns it made
+    ///
+    pub fn record_run(&mut self, score: u32, max_length: f32, moves: u64, turns: u64) {
+        self.games_played += 1;
+        self.total_moves += moves;
+        self.total_turns += turns;
+        self.total_score = self.total_score.saturating_add(score);
+        self.best_score = u32::max(self.best_score, score);
+        self.max_length_ever = f32::max(self.max_length_ever, max_length);
+    }
+
+    /// Return the mean score of all runs, 0 when none were played
+    ///
+    pub fn average_score(&self) -> f32 {
+        if self.games_played == 0 {
+            return 0.;
+        }
+
+        self.total_score as f32 / self.games_played as f32
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(consts::GAME_ID).join(consts::STATS_FILE))
+    }
+}
@@ -0,0 +1,141 @@
+//! Module with ColorTheme struct
+use ggez::graphics::{self, Color};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::game::maths;
+
+/// Structure with colors used to draw the game.
+/// Segments get colors fading from `head_color` to `tail_color` in HSL,
+/// so the colors in between do not turn muddy.
+/// The head itself is drawn with `head_accent_color`.
+/// Obstacles are drawn with `obstacle_color`.
+///
+/// Background image is tinted with `background_color` and normal food with `food_color`,
+/// other food kinds keep their own colors, so their effects can be told apart.
+/// All plain texts are drawn in `text_color`.
+/// With `has_glow` the snake, obstacles and food get a translucent bloom around them.
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct ColorTheme {
+    pub head_color: Color,
+    pub tail_color: Color,
+    pub head_accent_color: Color,
+    pub obstacle_color: Color,
+    pub background_color: Color,
+    pub food_color: Color,
+    pub text_color: Color,
+    pub has_glow: bool,
+}
+
+impl Default for ColorTheme {
+    /// Bright green body fading into a dark green tail, with an orange head
+    ///
+    fn default() -> Self {
+        Self {
+            head_color: Color::from_rgb(80, 255, 80),
+            tail_color: Color::from_rgb(0, 90, 20),
+            head_accent_color: Color::from_rgb(255, 150, 30),
+            obstacle_color: Color::from_rgb(110, 80, 60),
+            background_color: graphics::WHITE,
+            food_color: graphics::WHITE,
+            text_color: graphics::WHITE,
+            has_glow: false,
+        }
+    }
+}
+
+/// Enumeration of the themes that can be chosen in the settings
+///
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum ThemeName {
+    Classic,
+    Night,
+    Neon,
+}
+
+impl ThemeName {
+    /// Returns all themes, in the order they are cycled through
+    ///
+    pub fn all() -> [ThemeName; 3] {
+        [ThemeName::Classic, ThemeName::Night, ThemeName::Neon]
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ThemeName::Classic => "Classic",
+            ThemeName::Night => "Night",
+            ThemeName::Neon => "Neon",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ColorTheme {
+    /// Returns the theme with the given name
+    ///
+    pub fn from_name(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Classic => Self::default(),
+            // muted moonlight colors
+            ThemeName::Night => Self {
+                head_color: Color::from_rgb(150, 170, 230),
+                tail_color: Color::from_rgb(30, 30, 80),
+                head_accent_color: Color::from_rgb(240, 230, 140),
+                obstacle_color: Color::from_rgb(50, 50, 70),
+                background_color: Color::from_rgb(70, 80, 130),
+                food_color: Color::from_rgb(255, 240, 180),
+                text_color: Color::from_rgb(210, 220, 255),
+                has_glow: false,
+            },
+            // saturated magenta and cyan
+            ThemeName::Neon => Self {
+                head_color: Color::from_rgb(255, 40, 220),
+                tail_color: Color::from_rgb(40, 0, 160),
+                head_accent_color: Color::from_rgb(0, 255, 240),
+                obstacle_color: Color::from_rgb(0, 200, 120),
+                background_color: Color::from_rgb(60, 30, 90),
+                food_color: Color::from_rgb(255, 255, 80),
+                text_color: Color::from_rgb(0, 255, 240),
+                has_glow: true,
+            },
+        }
+    }
+
+    /// Blue colors of the second player snake, with a pink head.
+    /// Everything else is taken from this theme.
+    ///
+    pub fn player_two(&self) -> Self {
+        Self {
+            head_color: Color::from_rgb(90, 170, 255),
+            tail_color: Color::from_rgb(10, 30, 120),
+            head_accent_color: Color::from_rgb(255, 90, 200),
+            ..*self
+        }
+    }
+
+    /// Return the color of a segment, where index 0 is the head
+    /// and `total_segments - 1` is the tail.
+    ///
+    pub fn segment_color(&self, segment_index: usize, total_segments: usize) -> Color {
+        let t = if total_segments > 1 {
+            segment_index as f32 / (total_segments - 1) as f32
+        } else {
+            0.
+        };
+        let (head, tail) = (self.head_color, self.tail_color);
+        let (head_h, head_s, head_l) = maths::rgb_to_hsl(head.r, head.g, head.b);
+        let (tail_h, tail_s, tail_l) = maths::rgb_to_hsl(tail.r, tail.g, tail.b);
+        // gray has no hue of its own, so it takes the one of the other end
+        let head_h = if head_s > 0. { head_h } else { tail_h };
+        let tail_h = if tail_s > 0. { tail_h } else { head_h };
+        let (r, g, b) = maths::hsl_to_rgb(
+            maths::lerp_hue(head_h, tail_h, t),
+            maths::lerp(head_s, tail_s, t),
+            maths::lerp(head_l, tail_l, t),
+        );
+        Color::new(r, g, b, maths::lerp(head.a, tail.a, t))
+    }
+}
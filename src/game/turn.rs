@@ -1,12 +1,11 @@
 use ggez::{
-    graphics::{self, Rect},
+    graphics::{self, Color, Mesh, MeshBuilder, Rect},
     Context,
 };
 
-#[cfg(feature = "debug")]
-use ggez::graphics::{Color, Mesh};
+use serde::{Deserialize, Serialize};
 
-use crate::game::{coords::Coords, direction::Direction};
+use crate::game::{coords::Coords, direction::Direction, maths, savestate::SegmentSnapshot};
 
 use super::{
     consts,
@@ -16,9 +15,10 @@ use super::{
 
 /// Curved segment of a snake, 0-90 degrees of a ring.
 ///
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Turn {
     pub percentage: f32,
+    pub prev_percentage: f32,
     pub is_growing: bool,
     pub pos: Coords,
     pub in_dir: Direction,
@@ -32,12 +32,26 @@ impl Turn {
     pub fn new(pos: Coords, in_dir: Direction, out_dir: Direction) -> Self {
         Self {
             percentage: 0.,
+            prev_percentage: 0.,
             is_growing: true,
             pos,
             in_dir,
             out_dir,
         }
     }
+
+    /// Return the point on the middle of the turn, `progress` of the way from `pos`
+    /// to the end. It goes along a quarter of a circle that is centered
+    /// half of the width away from `pos`, towards `out_dir`.
+    ///
+    fn arc_point(&self, progress: f32) -> Coords {
+        let angle = progress * std::f32::consts::FRAC_PI_2;
+        let (sin, cos) = angle.sin_cos();
+        let center = self.pos + self.out_dir.as_coords() * consts::SNAKE_HALF_WIDTH;
+        center
+            + (self.in_dir.as_coords() * sin - self.out_dir.as_coords() * cos)
+                * consts::SNAKE_HALF_WIDTH
+    }
 }
 
 impl Growable for Turn {
@@ -68,6 +82,19 @@ impl Growable for Turn {
         left
     }
 
+    fn size(&self) -> f32 {
+        self.percentage * consts::SNAKE_WIDTH
+    }
+
+    fn beg(&self) -> Coords {
+        if self.is_growing {
+            self.pos
+        } else {
+            // shrinking turn is eaten from the `pos` side
+            self.arc_point(1. - self.percentage)
+        }
+    }
+
     fn end(&self) -> Coords {
         self.pos
             + self.in_dir.as_coords() * consts::SNAKE_HALF_WIDTH
@@ -77,6 +104,18 @@ impl Growable for Turn {
     fn direction(&self) -> Direction {
         self.out_dir
     }
+
+    fn shift(&mut self, offset: Coords) {
+        self.pos += offset;
+    }
+
+    fn store_previous(&mut self) {
+        self.prev_percentage = self.percentage;
+    }
+
+    fn snapshot(&self) -> SegmentSnapshot {
+        SegmentSnapshot::Turn(*self)
+    }
 }
 
 impl Renderable for Turn {
@@ -97,7 +136,11 @@ impl Renderable for Turn {
         Rect::new(x, y, consts::SNAKE_WIDTH, consts::SNAKE_WIDTH)
     }
 
-    fn draw(&self, ctx: &mut Context) {
+    fn tip(&self, alpha: f32) -> Coords {
+        self.arc_point(maths::lerp(self.prev_percentage, self.percentage, alpha))
+    }
+
+    fn add_to_mesh(&self, builder: &mut MeshBuilder, alpha: f32, color: Color) {
         let turn_type = TurnType::from_dirs(&self.in_dir, &self.out_dir);
         let (margin, is_reversed) = match turn_type {
             TurnType::DownRight => (Coords { x: 1., y: -1. }, self.out_dir == Direction::Up),
@@ -109,32 +152,30 @@ impl Renderable for Turn {
         let pos = self.pos
             + self.in_dir.as_coords() * consts::SNAKE_HALF_WIDTH
             + margin * consts::HALF_TURN_MARGIN;
-        if let Ok(mesh) = Renderer::create_qt_ring(
-            ctx,
+        // degenerate arcs cannot be tessellated, they are too small to be seen anyway
+        let _ = Renderer::add_qt_ring(
+            builder,
             pos,
             consts::SNAKE_WIDTH + consts::TURN_MARGIN,
             consts::TURN_MARGIN,
             turn_type,
-            self.percentage,
+            maths::lerp(self.prev_percentage, self.percentage, alpha),
             is_reversed ^ self.is_growing,
             true,
-        ) {
-            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
-                .expect("Error while drawing Turn");
-        }
+            color,
+        );
+    }
 
-        #[cfg(feature = "debug")]
-        {
-            let mesh = Mesh::new_rectangle(
-                ctx,
-                graphics::DrawMode::stroke(1.),
-                self.get_bbox(),
-                Color::from_rgb(255, 0, 0),
-            )
-            .unwrap();
-            graphics::draw(ctx, &mesh, graphics::DrawParam::default())
-                .expect("Error while drawing Turn border");
-        }
+    fn draw_debug(&self, ctx: &mut Context, _alpha: f32) {
+        let mesh = Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(1.),
+            self.bounding_box(),
+            Color::from_rgb(255, 0, 0),
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+            .expect("Error while drawing Turn border");
     }
 }
 
@@ -197,3 +238,32 @@ pub fn _get_arc_translation(quarter: TurnType) -> Coords {
         TurnType::UpRight => Coords::new(0.5, 0.5),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(rect: Rect, p: Coords) -> bool {
+        p.x >= rect.left() - consts::POINT_EPSILON
+            && p.x <= rect.right() + consts::POINT_EPSILON
+            && p.y >= rect.top() - consts::POINT_EPSILON
+            && p.y <= rect.bottom() + consts::POINT_EPSILON
+    }
+
+    #[test]
+    fn bounding_box_covers_the_arc_of_every_turn() {
+        for &in_dir in &Direction::all() {
+            for &out_dir in &[in_dir.rotate_cw(), in_dir.rotate_ccw()] {
+                let mut turn = Turn::new(Coords::new(100., 100.), in_dir, out_dir);
+                turn.grow(consts::SNAKE_WIDTH);
+                let bbox = turn.bounding_box();
+                assert_eq!((bbox.w, bbox.h), (consts::SNAKE_WIDTH, consts::SNAKE_WIDTH));
+                for step in 0..=10 {
+                    let point = turn.arc_point(step as f32 / 10.);
+                    assert!(contains(bbox, point), "{:?} -> {:?}", in_dir, out_dir);
+                }
+                assert!(contains(bbox, turn.end()), "{:?} -> {:?}", in_dir, out_dir);
+            }
+        }
+    }
+}
@@ -8,20 +8,56 @@
 //!
 //! Uses [ggez crate](https://crates.io/crates/ggez) for game engine related stuff.
 
-use game::{GameData, GAME_AUTHOR, GAME_ID, SCREEN_SIZE};
+use clap::Parser;
+use game::{run_benchmark, run_headless, BENCHMARK_GAMES};
+use game::{GameConfig, GameData, GameMode, StartupArgs, CONFIG_FILE, GAME_AUTHOR, GAME_ID};
 use ggez::{
     conf,
     event::{self},
 };
-use ggez::{ContextBuilder, GameResult};
-use std::path;
+use ggez::{ContextBuilder, GameError, GameResult};
+use std::{path, process};
 mod game;
 
-/// Main function that sets-up the window, creates GameData and runs the main game loop.
+/// Reads the config file given by the `--config` argument.
+/// Without it, reads `CONFIG_FILE`, when there is one, or falls back to the default config.
+///
+fn load_config(args: &StartupArgs) -> GameResult<GameConfig> {
+    match &args.config {
+        Some(config_path) => GameConfig::from_toml(config_path),
+        None => {
+            let config_path = path::Path::new(CONFIG_FILE);
+            if config_path.exists() {
+                GameConfig::from_toml(config_path)
+            } else {
+                Ok(GameConfig::default())
+            }
+        }
+    }
+}
+
+/// Main function that parses the arguments, sets-up the window,
+/// creates GameData and runs the main game loop.
 ///
 fn main() -> GameResult {
+    let args = StartupArgs::parse();
+    let config = load_config(&args)?;
+    let mode = GameMode::from_name(&args.mode, &config).map_err(GameError::ConfigError)?;
+    if args.benchmark {
+        println!("{}", run_benchmark(&config, BENCHMARK_GAMES));
+        return Ok(());
+    }
+    if let Some(games) = args.headless {
+        run_headless(&config, args.seed, games);
+        return Ok(());
+    }
+
     let window_setup = conf::WindowSetup::default().title(GAME_ID);
-    let window_mode = conf::WindowMode::default().dimensions(SCREEN_SIZE.x, SCREEN_SIZE.y);
+    let mut window_mode =
+        conf::WindowMode::default().dimensions(config.screen_width, config.screen_height);
+    if args.fullscreen {
+        window_mode = window_mode.fullscreen_type(conf::FullscreenType::Desktop);
+    }
     let resource_path = path::PathBuf::from("./resources");
 
     let (ref mut ctx, ref mut event_loop) = ContextBuilder::new(GAME_ID, GAME_AUTHOR)
@@ -30,6 +66,20 @@ fn main() -> GameResult {
         .add_resource_path(resource_path)
         .build()?;
 
-    let game_state = &mut GameData::new(ctx);
+    let game_data = match &args.replay {
+        Some(replay_path) => GameData::new_playback(ctx, &config, replay_path),
+        None if mode == GameMode::DailyChallenge => GameData::new_daily(ctx, &config, &args),
+        None => GameData::new(ctx, &config, &args, mode),
+    };
+    let game_state = &mut match game_data {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("Could not start {}: {}", GAME_ID, err);
+            if let GameError::ResourceNotFound(..) = err {
+                eprintln!("Make sure the resources directory lies next to the game.");
+            }
+            process::exit(1);
+        }
+    };
     event::run(ctx, event_loop, game_state)
 }